@@ -16,37 +16,371 @@
 
 //! Adapters to work with `frame_support::traits::Currency` through XCM.
 
-use frame_support::traits::{ExistenceRequirement::AllowDeath, Get, WithdrawReasons};
-use sp_runtime::traits::{CheckedSub, SaturatedConversion};
-use sp_std::{convert::TryInto, marker::PhantomData, result};
-use xcm::latest::{Error as XcmError, MultiAsset, MultiLocation, Result};
+use frame_support::traits::{
+	Contains,
+	ExistenceRequirement::{self, AllowDeath, KeepAlive},
+	Get, Imbalance, LockIdentifier, LockableCurrency, WithdrawReasons,
+};
+use sp_runtime::{
+	traits::{Bounded, CheckedAdd, CheckedSub, Saturating, Zero},
+	DispatchError, TokenError, TransactionOutcome,
+};
+use sp_std::{
+	convert::{TryFrom, TryInto},
+	fmt::Debug,
+	marker::PhantomData,
+	result,
+};
+use xcm::latest::{
+	AssetId, Error as XcmError,
+	Fungibility::{Fungible, NonFungible},
+	MultiAsset, MultiAssets, MultiLocation, Result,
+};
 use xcm_executor::{
 	traits::{Convert, MatchesFungible, TransactAsset},
 	Assets,
 };
 
-/// Asset transaction errors.
-enum Error {
-	/// Asset not found.
-	AssetNotFound,
+/// Asset transaction errors. Conversion failures carry the offending input, which is logged when
+/// a failed operation is reported.
+#[derive(Debug)]
+enum CurrencyAdapterError {
+	/// The matcher does not handle this asset.
+	AssetNotFound(MultiAsset),
 	/// `MultiLocation` to `AccountId` conversion failed.
-	AccountIdConversionFailed,
-	/// `u128` amount to currency `Balance` conversion failed.
-	AmountToBalanceConversionFailed,
+	AccountIdConversionFailed(MultiLocation),
+	/// `u128` amount of this asset to currency `Balance` conversion failed.
+	AmountToBalanceConversionFailed(MultiAsset),
+	/// The asset amount is zero, so no value would actually be moved.
+	ZeroAmount,
+	/// The deposit would create a new account with less than the minimum balance.
+	BelowMinimumBalance,
+	/// A teleport needs accounting but no checking account is configured.
+	NoCheckingAccount,
+	/// A reserve-backed deposit has no reserve location configured.
+	NoReserveLocation,
+	/// The account does not have enough funds.
+	InsufficientBalance,
+	/// The funds are present but locked or frozen.
+	BalanceLocked,
+	/// The asset has the currency's identity but is non-fungible.
+	ExpectedFungible(MultiAsset),
+	/// Any other error reported by the currency.
+	Dispatch(&'static str),
+	/// Minting the amount would overflow the total issuance.
+	IssuanceOverflow,
+	/// The needed part of a withdrawal with change is not a fungible of the requested asset.
+	ChangeMismatch,
+	/// A withdrawal with change yielded less than the needed amount, e.g. due to rounding.
+	NeedNotCovered,
+	/// The account has less than the amount locked, so unlocking it would back nothing.
+	NotLocked,
+	/// The adapter cannot move funds between accounts.
+	TransferNotSupported,
 }
 
-impl From<Error> for XcmError {
-	fn from(e: Error) -> Self {
+impl From<CurrencyAdapterError> for XcmError {
+	fn from(e: CurrencyAdapterError) -> Self {
+		use CurrencyAdapterError::*;
 		use XcmError::FailedToTransactAsset;
 		match e {
-			Error::AssetNotFound => XcmError::AssetNotFound,
-			Error::AccountIdConversionFailed => FailedToTransactAsset("AccountIdConversionFailed"),
-			Error::AmountToBalanceConversionFailed =>
+			AssetNotFound(_) => XcmError::AssetNotFound,
+			AccountIdConversionFailed(_) => FailedToTransactAsset("AccountIdConversionFailed"),
+			AmountToBalanceConversionFailed(_) =>
 				FailedToTransactAsset("AmountToBalanceConversionFailed"),
+			ExpectedFungible(_) => FailedToTransactAsset("ExpectedFungible"),
+			ZeroAmount => FailedToTransactAsset("ZeroAmount"),
+			BelowMinimumBalance => FailedToTransactAsset("BelowMinimumBalance"),
+			NoCheckingAccount => FailedToTransactAsset("NoCheckingAccount"),
+			NoReserveLocation => FailedToTransactAsset("NoReserveLocation"),
+			InsufficientBalance => FailedToTransactAsset("InsufficientBalance"),
+			BalanceLocked => FailedToTransactAsset("BalanceLocked"),
+			Dispatch(e) => FailedToTransactAsset(e),
+			IssuanceOverflow => FailedToTransactAsset("IssuanceOverflow"),
+			ChangeMismatch => FailedToTransactAsset("ChangeMismatch"),
+			NeedNotCovered => FailedToTransactAsset("NeedNotCovered"),
+			NotLocked => FailedToTransactAsset("NotLocked"),
+			TransferNotSupported => FailedToTransactAsset("TransferNotSupported"),
+		}
+	}
+}
+
+impl From<DispatchError> for CurrencyAdapterError {
+	fn from(e: DispatchError) -> Self {
+		match e {
+			DispatchError::Token(TokenError::NoFunds) => Self::InsufficientBalance,
+			DispatchError::Token(TokenError::Frozen) => Self::BalanceLocked,
+			DispatchError::Token(TokenError::WouldDie) |
+			DispatchError::Token(TokenError::BelowMinimum) => Self::BelowMinimumBalance,
+			// Errors of `pallet-balances`, identified by name.
+			e => match <&'static str>::from(e) {
+				"InsufficientBalance" => Self::InsufficientBalance,
+				"LiquidityRestrictions" => Self::BalanceLocked,
+				"ExistentialDeposit" | "KeepAlive" => Self::BelowMinimumBalance,
+				other => Self::Dispatch(other),
+			},
 		}
 	}
 }
 
+/// The error for an asset which `Matcher` did not match.
+///
+/// A non-fungible asset whose identity the matcher does handle is reported as `ExpectedFungible`;
+/// anything else is `AssetNotFound`, so that other transactors in a tuple still get to handle it.
+fn not_matched<Balance, Matcher: MatchesFungible<Balance>>(
+	what: &MultiAsset,
+) -> CurrencyAdapterError {
+	match what.fun {
+		NonFungible(_)
+			if Matcher::matches_fungible(&MultiAsset { id: what.id.clone(), fun: Fungible(1) })
+				.is_some() =>
+			CurrencyAdapterError::ExpectedFungible(what.clone()),
+		_ => CurrencyAdapterError::AssetNotFound(what.clone()),
+	}
+}
+
+/// How an amount which does not fit into the type it is converted to is treated. This applies
+/// both to XCM amounts converted into the currency's `Balance` type and to matched balances
+/// converted into `u128` for XCM, which a `Balance` wider than `u128` may exceed.
+///
+/// Saturating silently changes the amount transacted, so chains opting into it should make sure
+/// the amounts involved can never reach the limits.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AmountConversion {
+	/// Fail the transaction with `AmountToBalanceConversionFailed`.
+	Reject,
+	/// Cap the amount at the largest value of the target type.
+	Saturate,
+}
+
+impl AmountConversion {
+	/// Convert `amount` into a `Balance` according to this policy.
+	pub fn convert<Balance: TryFrom<u128> + Bounded>(self, amount: u128) -> Option<Balance> {
+		match self {
+			AmountConversion::Reject => amount.try_into().ok(),
+			AmountConversion::Saturate =>
+				Some(amount.try_into().unwrap_or_else(|_| Balance::max_value())),
+		}
+	}
+
+	/// Convert a matched `amount` into a `u128` according to this policy.
+	pub fn balance_to_u128<Balance: TryInto<u128>>(self, amount: Balance) -> Option<u128> {
+		match self {
+			AmountConversion::Reject => amount.try_into().ok(),
+			AmountConversion::Saturate => Some(amount.try_into().unwrap_or(u128::MAX)),
+		}
+	}
+}
+
+/// Which way amounts scaled by `CurrencyAdapterConfig::rate` are rounded.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rounding {
+	/// Round towards zero, truncating the fractional part.
+	Down,
+	/// Round away from zero, so that any fractional part costs a whole local unit.
+	Up,
+}
+
+/// The rate `(numerator, denominator)` converting amounts of an asset with `remote_decimals` into
+/// one with `local_decimals`, for use as `CurrencyAdapterConfig::rate_for`. E.g. an asset with 12
+/// decimals elsewhere and 10 locally gives `(1, 100)`. `None` if the decimals differ by more than
+/// a `u128` can scale, i.e. 38.
+pub fn decimals_rate(remote_decimals: u8, local_decimals: u8) -> Option<(u128, u128)> {
+	if remote_decimals > local_decimals {
+		Some((1, 10u128.checked_pow((remote_decimals - local_decimals).into())?))
+	} else {
+		Some((10u128.checked_pow((local_decimals - remote_decimals).into())?, 1))
+	}
+}
+
+/// Where the funds of a deposit come from, and where those of a withdrawal go. Transfers between
+/// two accounts move the funds of the sender whatever the strategy: they either use
+/// `Currency::transfer`, or burn the withdrawn funds and mint them into the receiver.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DepositStrategy {
+	/// Mint the funds with `deposit_creating`, increasing the total issuance, and burn withdrawn
+	/// funds. Appropriate when the funds were burnt elsewhere, e.g. by a withdrawal on this chain or
+	/// a teleport from another one which is accounted for by the checking account.
+	Mint,
+	/// Transfer the funds out of the checking account, and credit withdrawn funds to it, leaving the
+	/// total issuance unchanged. Appropriate when this chain is the reserve of the asset and
+	/// deposits are backed by funds it already holds, so minting them would inflate the supply. The
+	/// checking account then holds whatever was withdrawn, including teleported funds, so
+	/// `check_in` and `check_out` leave it alone. Fails with `NoCheckingAccount` if
+	/// `CheckedAccount` gives `None`.
+	TransferFromCheckingAccount,
+	/// Transfer the funds out of the account which `AccountIdConverter` gives for the reserve
+	/// location of the asset, and credit withdrawn funds to it, leaving the total issuance
+	/// unchanged. Appropriate for reserve-backed assets, whose local representation must stay
+	/// backed 1:1 by what the reserve holds here. As the funds never leave the reserve account,
+	/// `check_in` and `check_out` leave the checking account alone. The reserve account is kept
+	/// alive. Fails with `NoReserveLocation` if `CurrencyAdapterConfig::reserve_location` gives
+	/// `None`.
+	TransferFromReserveAccount,
+}
+
+/// How `deposit_asset` and `withdraw_asset` treat assets which the matcher does not handle.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UnknownAssetPolicy {
+	/// Fail with `AssetNotFound`, which lets a tuple of transactors try the next one.
+	Reject,
+	/// Log the asset and succeed without doing anything; a withdrawal yields no assets. This hides
+	/// the asset from any transactor later in a tuple, so it is only meant for the single
+	/// transactor of a chain during a migration, and must not be the terminal transactor for
+	/// untrusted input, whose deposits of unknown assets would silently vanish.
+	Ignore,
+}
+
+/// What happens to a free balance which a withdrawal would leave below the minimum balance.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DustHandling {
+	/// Let the currency reap the account, handling the dust however it usually does.
+	Reap,
+	/// Fail the withdrawal with `BelowMinimumBalance`. Withdrawing the whole balance is still
+	/// allowed.
+	Forbid,
+	/// Withdraw the dust along with the amount and pass it to `on_dust`.
+	Sweep,
+}
+
+/// Optional behaviour of a [`CurrencyAdapter`].
+///
+/// Every item has a default which matches the plain adapter, so `()` can be used whenever no
+/// customisation is needed.
+pub trait CurrencyAdapterConfig<AccountId, Currency: frame_support::traits::Currency<AccountId>> {
+	/// Whether the reserved balance of an account may be used to cover a withdrawal which its free
+	/// balance alone cannot. The shortfall is moved into the free balance with [`Self::unreserve`]
+	/// before withdrawing, which must then be implemented as well.
+	const INCLUDE_RESERVED: bool = false;
+
+	/// Whether teleports must be tracked through a checking account. If set and an asset has no
+	/// checking account, `can_check_in` and `can_check_out` fail with `NoCheckingAccount` instead of
+	/// letting the teleport through unaccounted, and `check_in` and `check_out`, which cannot fail,
+	/// log an error.
+	const REQUIRE_CHECKING_ACCOUNT: bool = false;
+
+	/// The reasons given for every withdrawal, which determine the balance locks that apply. Chains
+	/// may e.g. use `TRANSFER | FEE` to subject XCM withdrawals to fee locks as well.
+	const WITHDRAW_REASONS: WithdrawReasons = WithdrawReasons::TRANSFER;
+
+	/// How amounts which do not fit into `Currency::Balance`, or matched balances which do not fit
+	/// into `u128`, are treated.
+	const AMOUNT_CONVERSION: AmountConversion = AmountConversion::Reject;
+
+	/// The existence requirement of withdrawals from the checking account. With the default of
+	/// `KeepAlive` the account is never reaped, so it must hold the minimum balance on top of the
+	/// teleported amount; `AllowDeath` lets the teleported amount drain it completely.
+	const CHECKING_ACCOUNT_EXISTENCE: ExistenceRequirement = KeepAlive;
+
+	/// Whether `check_out` into an empty checking account mints the minimum balance on top of the
+	/// teleported amount. Together with `KeepAlive` the account then always holds the minimum
+	/// balance besides the teleport liability, so everything teleported out can be checked back in
+	/// and no existential deposit is lost to reaping, however often the liability drops to zero.
+	/// `reconcile_checking_account` reports the minimum balance as a surplus.
+	const FUND_CHECKING_ACCOUNT: bool = false;
+
+	/// Where the funds of a deposit come from.
+	const DEPOSIT_STRATEGY: DepositStrategy = DepositStrategy::Mint;
+
+	/// What happens to a free balance which a withdrawal would leave below the minimum balance.
+	const DUST_HANDLING: DustHandling = DustHandling::Reap;
+
+	/// How `deposit_asset` and `withdraw_asset` treat assets which the matcher does not handle.
+	const UNKNOWN_ASSET_POLICY: UnknownAssetPolicy = UnknownAssetPolicy::Reject;
+
+	/// How `withdraw_asset` rounds amounts scaled by `rate`. Deposits always round down. Chains
+	/// which withdraw fees through the adapter should round up, so that a fee is never undercharged
+	/// by the fraction lost to truncation; the amount reported as withdrawn then covers the whole
+	/// local units taken.
+	const WITHDRAW_ROUNDING: Rounding = Rounding::Down;
+
+	/// The rate `(numerator, denominator)` by which XCM amounts are multiplied to give local
+	/// balances, for chains which express the asset in different units. The result is rounded
+	/// down unless `WITHDRAW_ROUNDING` says otherwise, so amounts worth less than one local unit are
+	/// rejected as zero; the amount reported as withdrawn is converted back and rounded down as
+	/// well. Amounts which overflow are rejected with `AmountToBalanceConversionFailed`. Usually
+	/// implemented with a `Get<(u128, u128)>`.
+	fn rate() -> (u128, u128) {
+		(1, 1)
+	}
+
+	/// The rate for `what` in particular, for adapters whose matcher accepts assets with differing
+	/// decimals, e.g. keyed by the location the asset comes from; see [`decimals_rate`]. By default
+	/// the `rate` common to all assets. `None` rejects the asset with
+	/// `AmountToBalanceConversionFailed`.
+	fn rate_for(_what: &MultiAsset) -> Option<(u128, u128)> {
+		Some(Self::rate())
+	}
+
+	/// Move up to `amount` of the reserved balance of `who` into its free balance, returning the
+	/// part which could not be moved. Usually implemented with `ReservableCurrency::unreserve`.
+	/// The default moves nothing, so that only the free balance can be withdrawn even under
+	/// `INCLUDE_RESERVED`; `can_withdraw_asset` runs it as well, so it agrees with the withdrawal
+	/// in either case.
+	fn unreserve(_who: &AccountId, amount: Currency::Balance) -> Currency::Balance {
+		amount
+	}
+
+	/// Called after `amount` of `what` has been successfully deposited into `who`.
+	fn on_deposit(_who: &AccountId, _what: &MultiAsset, _amount: Currency::Balance) {}
+
+	/// Called after `amount` of `what` has been successfully withdrawn from `who`.
+	fn on_withdraw(_who: &AccountId, _what: &MultiAsset, _amount: Currency::Balance) {}
+
+	/// Called when depositing `what` into, or withdrawing it from, `who` failed with `error`.
+	/// Assets which the adapter does not handle at all are not reported.
+	///
+	/// Together with `on_deposit` and `on_withdraw` this can drive metrics of the XCM asset flow.
+	fn on_failure(_what: &MultiAsset, _who: &MultiLocation, _error: &XcmError) {}
+
+	/// Observe the imbalance created by any withdrawal, e.g. for metrics, before it is dropped,
+	/// which burns the withdrawn funds. The handler only gets to see it: the withdrawn assets go on
+	/// into the holding register, to be deposited or teleported elsewhere, so keeping the funds
+	/// would create them twice. XCM fees are collected by the `WeightTrader` instead.
+	fn on_unbalanced(_amount: &Currency::NegativeImbalance) {}
+
+	/// Handle the dust swept up under [`DustHandling::Sweep`], e.g. by moving it into a treasury
+	/// account. By default it is dropped, which burns it.
+	fn on_dust(_dust: Currency::NegativeImbalance) {}
+
+	/// The checking account which tracks teleports of `what`, if it is not the global
+	/// `CheckedAccount`. This keeps the teleport liabilities of distinct assets handled by one
+	/// adapter apart.
+	fn checking_account_for(_what: &MultiAsset) -> Option<AccountId> {
+		None
+	}
+
+	/// The reserve location of `what`, whose account funds deposits under
+	/// [`DepositStrategy::TransferFromReserveAccount`]. None by default.
+	fn reserve_location(_what: &MultiAsset) -> Option<MultiLocation> {
+		None
+	}
+
+	/// Record the teleport `nonce` given to `check_in_once` or `check_out_once`, returning whether
+	/// it had not been recorded before. Integrators keep the nonces in storage of their choosing,
+	/// bounded as they see fit. By default nothing is recorded, so every nonce is new.
+	fn note_nonce(_nonce: u64) -> bool {
+		true
+	}
+}
+
+impl<AccountId, Currency: frame_support::traits::Currency<AccountId>>
+	CurrencyAdapterConfig<AccountId, Currency> for ()
+{
+}
+
+/// How the balance of a checking account compares to the teleport liability it should cover.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CheckingAccountDelta<Balance> {
+	/// The balance matches the liability.
+	Balanced,
+	/// The account holds this much more than the liability.
+	Surplus(Balance),
+	/// The account holds this much less than the liability, e.g. because a teleport was checked in
+	/// but never deposited.
+	Deficit(Balance),
+}
+
 /// Simple adapter to use a currency as asset transactor. This type can be used as `type AssetTransactor` in
 /// `xcm::Config`.
 ///
@@ -85,91 +419,1011 @@ impl From<Error> for XcmError {
 ///     CheckingAccount,
 /// >;
 /// ```
-pub struct CurrencyAdapter<Currency, Matcher, AccountIdConverter, AccountId, CheckedAccount>(
-	PhantomData<(Currency, Matcher, AccountIdConverter, AccountId, CheckedAccount)>,
-);
+///
+/// An optional sixth parameter implementing [`CurrencyAdapterConfig`] may be given to customise the
+/// adapter's behaviour; it defaults to `()`.
+pub struct CurrencyAdapter<
+	Currency,
+	Matcher,
+	AccountIdConverter,
+	AccountId,
+	CheckedAccount,
+	Config = (),
+>(PhantomData<(Currency, Matcher, AccountIdConverter, AccountId, CheckedAccount, Config)>);
 
 impl<
 		Matcher: MatchesFungible<Currency::Balance>,
 		AccountIdConverter: Convert<MultiLocation, AccountId>,
 		Currency: frame_support::traits::Currency<AccountId>,
-		AccountId: Clone, // can't get away without it since Currency is generic over it.
+		AccountId: Clone + Debug, // can't get away without it since Currency is generic over it.
 		CheckedAccount: Get<Option<AccountId>>,
+		Config: CurrencyAdapterConfig<AccountId, Currency>,
+	> CurrencyAdapter<Currency, Matcher, AccountIdConverter, AccountId, CheckedAccount, Config>
+{
+	/// Match `what` and convert its amount into a non-zero `Currency::Balance`.
+	fn matched_amount(
+		what: &MultiAsset,
+	) -> result::Result<Currency::Balance, CurrencyAdapterError> {
+		Self::matched_amount_rounded(what, Rounding::Down)
+	}
+
+	/// Like `matched_amount`, rounding the scaled amount as given.
+	fn matched_amount_rounded(
+		what: &MultiAsset,
+		rounding: Rounding,
+	) -> result::Result<Currency::Balance, CurrencyAdapterError> {
+		let amount = Self::matched_u128(what)?;
+		if amount.is_zero() {
+			return Err(CurrencyAdapterError::ZeroAmount)
+		}
+		let (numerator, denominator) = Config::rate_for(what)
+			.ok_or_else(|| CurrencyAdapterError::AmountToBalanceConversionFailed(what.clone()))?;
+		let amount = amount
+			.checked_mul(numerator)
+			.and_then(|amount| {
+				let quotient = amount.checked_div(denominator)?;
+				match rounding {
+					Rounding::Up if amount % denominator != 0 => quotient.checked_add(1),
+					_ => Some(quotient),
+				}
+			})
+			.ok_or_else(|| CurrencyAdapterError::AmountToBalanceConversionFailed(what.clone()))?;
+		if amount.is_zero() {
+			return Err(CurrencyAdapterError::ZeroAmount)
+		}
+		Config::AMOUNT_CONVERSION
+			.convert(amount)
+			.ok_or_else(|| CurrencyAdapterError::AmountToBalanceConversionFailed(what.clone()))
+	}
+
+	/// Match `what`, giving its amount as a `u128`. Matchers such as `IsConcrete` convert the amount
+	/// into `Currency::Balance` themselves and fail if it does not fit; such an asset is still
+	/// recognised by its identity, and its raw amount left to `AMOUNT_CONVERSION` once scaled.
+	fn matched_u128(what: &MultiAsset) -> result::Result<u128, CurrencyAdapterError> {
+		match (Matcher::matches_fungible(what), &what.fun) {
+			(Some(amount), _) => Config::AMOUNT_CONVERSION
+				.balance_to_u128(amount)
+				.ok_or_else(|| CurrencyAdapterError::AmountToBalanceConversionFailed(what.clone())),
+			(None, Fungible(amount))
+				if Matcher::matches_fungible(&MultiAsset {
+					id: what.id.clone(),
+					fun: Fungible(1),
+				})
+				.is_some() =>
+				Ok(*amount),
+			_ => Err(not_matched::<Currency::Balance, Matcher>(what)),
+		}
+	}
+
+	/// Convert a local `amount` of `what` back into the units of the XCM asset, rounding down. An
+	/// amount which does not fit into a `u128` is treated according to `AMOUNT_CONVERSION`.
+	fn xcm_amount(
+		what: &MultiAsset,
+		amount: Currency::Balance,
+	) -> result::Result<u128, CurrencyAdapterError> {
+		let conversion_failed =
+			|| CurrencyAdapterError::AmountToBalanceConversionFailed(what.clone());
+		let (numerator, denominator) = Config::rate_for(what).ok_or_else(conversion_failed)?;
+		let amount = Config::AMOUNT_CONVERSION
+			.balance_to_u128(amount)
+			.ok_or_else(conversion_failed)?;
+		let amount = match Config::AMOUNT_CONVERSION {
+			AmountConversion::Reject =>
+				amount.checked_mul(denominator).ok_or_else(conversion_failed)?,
+			AmountConversion::Saturate => amount.saturating_mul(denominator),
+		};
+		Ok(amount.checked_div(numerator).unwrap_or_default())
+	}
+
+	/// Convert `who` into the local account it controls, for callers which perform several
+	/// operations on the same location and want to convert it only once.
+	pub fn resolve_account(who: &MultiLocation) -> result::Result<AccountId, XcmError> {
+		Self::account(who).map_err(Into::into)
+	}
+
+	/// Convert `who` into the local account it controls.
+	fn account(who: &MultiLocation) -> result::Result<AccountId, CurrencyAdapterError> {
+		AccountIdConverter::convert_ref(who)
+			.map_err(|()| CurrencyAdapterError::AccountIdConversionFailed(who.clone()))
+	}
+
+	/// Like `check_in`, but a no-op if `nonce` was already used, so that a replayed message does not
+	/// check the same teleport in twice. Nonces are shared with `check_out_once`.
+	pub fn check_in_once(origin: &MultiLocation, what: &MultiAsset, nonce: u64) {
+		if Config::note_nonce(nonce) {
+			<Self as TransactAsset>::check_in(origin, what);
+		} else {
+			log::debug!(target: "xcm::currency_adapter", "check_in skipped: replayed nonce: {}", nonce);
+		}
+	}
+
+	/// Like `check_out`, but a no-op if `nonce` was already used, so that a replayed message does
+	/// not check the same teleport out twice. Nonces are shared with `check_in_once`.
+	pub fn check_out_once(dest: &MultiLocation, what: &MultiAsset, nonce: u64) {
+		if Config::note_nonce(nonce) {
+			<Self as TransactAsset>::check_out(dest, what);
+		} else {
+			log::debug!(target: "xcm::currency_adapter", "check_out skipped: replayed nonce: {}", nonce);
+		}
+	}
+
+	/// Withdraw all of `assets` from `who`, resolving its account only once. Each asset is treated
+	/// as by `withdraw_asset`. If any asset cannot be withdrawn, all withdrawals are rolled back and
+	/// the error is returned. The failure is reported once the rollback is done, so that whatever
+	/// `Config::on_failure` writes to storage is kept.
+	pub fn withdraw_assets(
+		assets: &MultiAssets,
+		who: &MultiLocation,
+	) -> result::Result<Assets, XcmError> {
+		log::trace!(target: "xcm::currency_adapter", "withdraw_assets assets: {:?}, who: {:?}", assets, who);
+		let account = match assets.inner().first() {
+			Some(what) =>
+				Self::account(who).map_err(|e| Self::failed("withdraw_assets", what, who, e))?,
+			None => return Ok(Assets::new()),
+		};
+		frame_support::storage::with_transaction(|| {
+			let mut withdrawn = Assets::new();
+			for what in assets.inner() {
+				match Self::matched_amount_rounded(what, Config::WITHDRAW_ROUNDING).and_then(
+					|amount| Self::withdraw_from(what, amount, &account, Config::DEPOSIT_STRATEGY),
+				) {
+					Ok(assets) => withdrawn.subsume_assets(assets),
+					Err(e) if Self::ignored(&e) => {
+						log::debug!(target: "xcm::currency_adapter", "withdraw_assets ignored unknown asset: {:?}", what);
+					},
+					Err(e) => return TransactionOutcome::Rollback(Err((what, e))),
+				}
+			}
+			TransactionOutcome::Commit(Ok(withdrawn))
+		})
+		.map_err(|(what, e)| Self::failed("withdraw_assets", what, who, e))
+	}
+
+	/// Withdraw the larger of `requested` and `needed` from `who`, splitting the withdrawn assets
+	/// into the `needed` part, which is consumed, and the change. This supports over-estimating an
+	/// amount, e.g. of fees, and returning the unused remainder to the holding register. If less
+	/// than `needed` is withdrawn, e.g. because the matcher rounds it down, the withdrawal is rolled
+	/// back and `NeedNotCovered` returned.
+	pub fn withdraw_asset_with_change(
+		requested: &MultiAsset,
+		needed: &MultiAsset,
+		who: &MultiLocation,
+	) -> result::Result<(Assets, Assets), XcmError> {
+		let needed_amount = match needed.fun {
+			Fungible(amount) if needed.id == requested.id => amount,
+			_ =>
+				return Err(Self::failed(
+					"withdraw_asset_with_change",
+					needed,
+					who,
+					CurrencyAdapterError::ChangeMismatch,
+				)),
+		};
+		let larger = match requested.fun {
+			Fungible(amount) if amount < needed_amount => needed,
+			_ => requested,
+		};
+		let withdrawn_amount = frame_support::storage::with_transaction(|| {
+			let withdrawn = match Self::do_withdraw_asset(
+				larger,
+				who,
+				Config::WITHDRAW_ROUNDING,
+				Config::DEPOSIT_STRATEGY,
+			) {
+				Ok(withdrawn) => withdrawn,
+				Err(e) if Self::ignored(&e) => Assets::new(),
+				Err(e) => return TransactionOutcome::Rollback(Err((larger, e))),
+			};
+			match withdrawn.fungible.get(&requested.id).copied().unwrap_or_default() {
+				amount if amount < needed_amount => TransactionOutcome::Rollback(Err((
+					needed,
+					CurrencyAdapterError::NeedNotCovered,
+				))),
+				amount => TransactionOutcome::Commit(Ok(amount)),
+			}
+		})
+		.map_err(|(what, e)| Self::failed("withdraw_asset_with_change", what, who, e))?;
+		let assets = |amount| match amount {
+			0 => Assets::new(),
+			amount => MultiAsset { id: requested.id.clone(), fun: Fungible(amount) }.into(),
+		};
+		Ok((assets(needed_amount), assets(withdrawn_amount - needed_amount)))
+	}
+
+	/// The checking account which tracks teleports of `what`.
+	fn checking_account(what: &MultiAsset) -> Option<AccountId> {
+		Config::checking_account_for(what).or_else(CheckedAccount::get)
+	}
+
+	/// The minimum balance to mint into `checking_account` along with a teleport under
+	/// `FUND_CHECKING_ACCOUNT`, if it is empty.
+	fn checking_account_top_up(checking_account: &AccountId) -> Currency::Balance {
+		if Config::FUND_CHECKING_ACCOUNT && Currency::total_balance(checking_account).is_zero() {
+			Currency::minimum_balance()
+		} else {
+			Zero::zero()
+		}
+	}
+
+	/// Compare the free balance of the checking account of the asset `id` to the `expected`
+	/// teleport liability, i.e. the net amount teleported out and not yet back in. Only the free
+	/// balance counts, as `check_in` cannot withdraw anything else. Assets sharing a checking
+	/// account are reconciled together, so `expected` must then be their summed liability. This
+	/// changes no state; it is up to governance to act on a reported imbalance. Returns `None` if
+	/// there is no checking account.
+	pub fn reconcile_checking_account(
+		id: &AssetId,
+		expected: Currency::Balance,
+	) -> Option<CheckingAccountDelta<Currency::Balance>> {
+		let what = MultiAsset { id: id.clone(), fun: Fungible(0) };
+		let balance = Currency::free_balance(&Self::checking_account(&what)?);
+		Some(if balance > expected {
+			CheckingAccountDelta::Surplus(balance - expected)
+		} else if balance < expected {
+			CheckingAccountDelta::Deficit(expected - balance)
+		} else {
+			CheckingAccountDelta::Balanced
+		})
+	}
+
+	/// Verify that the checking account of the asset `id` covers exactly the
+	/// `expected_outstanding` teleport liability, as with `reconcile_checking_account`, returning
+	/// the absolute discrepancy otherwise. Without a checking account nothing is covered, so the
+	/// whole liability is the discrepancy. Meant for `try_runtime` checks and governance.
+	pub fn verify_teleport_invariant(
+		id: &AssetId,
+		expected_outstanding: Currency::Balance,
+	) -> result::Result<(), Currency::Balance> {
+		match Self::reconcile_checking_account(id, expected_outstanding) {
+			Some(CheckingAccountDelta::Balanced) => Ok(()),
+			Some(CheckingAccountDelta::Surplus(d) | CheckingAccountDelta::Deficit(d)) => Err(d),
+			None if expected_outstanding.is_zero() => Ok(()),
+			None => Err(expected_outstanding),
+		}
+	}
+
+	/// Check whether `withdraw_asset` of `what` from `who` would succeed, without changing any
+	/// state. Useful for estimating fees before committing to a withdrawal.
+	pub fn can_withdraw_asset(what: &MultiAsset, who: &MultiLocation) -> Result {
+		log::trace!(target: "xcm::currency_adapter", "can_withdraw_asset what: {:?}, who: {:?}", what, who);
+		let amount = Self::matched_amount_rounded(what, Config::WITHDRAW_ROUNDING)?;
+		let who = Self::account(who)?;
+		Self::credited_account(what, amount, Config::DEPOSIT_STRATEGY)?;
+		if !Config::INCLUDE_RESERVED {
+			return Self::can_withdraw_free(&who, amount).map_err(Into::into)
+		}
+		// Unreserve the shortfall as the withdrawal would and roll it back, so that the check sees
+		// exactly what `Config::unreserve` moves.
+		frame_support::storage::with_transaction(|| {
+			Self::unreserve_shortfall(&who, amount);
+			TransactionOutcome::Rollback(Self::can_withdraw_free(&who, amount))
+		})
+		.map_err(Into::into)
+	}
+
+	/// Check whether `amount` and any dust can be withdrawn from the free balance of `who`.
+	fn can_withdraw_free(
+		who: &AccountId,
+		amount: Currency::Balance,
+	) -> result::Result<(), CurrencyAdapterError> {
+		let free = Currency::free_balance(who);
+		let new_balance =
+			free.checked_sub(&amount).ok_or(CurrencyAdapterError::InsufficientBalance)?;
+		let dust = Self::dust(who, amount, free)?;
+		Currency::ensure_can_withdraw(
+			who,
+			amount + dust,
+			Config::WITHDRAW_REASONS,
+			new_balance - dust,
+		)
+		.map_err(CurrencyAdapterError::from)
+	}
+
+	/// The account which a withdrawal of `amount` of `what` credits under `strategy`, being the one
+	/// deposits are paid from, or `None` if the withdrawal burns the funds. Fails if the account
+	/// cannot take the amount, so that nothing is withdrawn.
+	fn credited_account(
+		what: &MultiAsset,
+		amount: Currency::Balance,
+		strategy: DepositStrategy,
+	) -> result::Result<Option<AccountId>, CurrencyAdapterError> {
+		let account = match strategy {
+			DepositStrategy::TransferFromCheckingAccount =>
+				Self::checking_account(what).ok_or(CurrencyAdapterError::NoCheckingAccount)?,
+			DepositStrategy::TransferFromReserveAccount => Self::reserve_account(what)?,
+			DepositStrategy::Mint => return Ok(None),
+		};
+		// `resolve_creating` burns amounts which cannot create the account.
+		if Currency::total_balance(&account).is_zero() && amount < Currency::minimum_balance() {
+			return Err(CurrencyAdapterError::BelowMinimumBalance)
+		}
+		Ok(Some(account))
+	}
+
+	/// Whether `check_in` and `check_out` account for teleports by burning and minting funds in the
+	/// checking account. This is only needed if withdrawals burn the funds and deposits mint them:
+	/// otherwise the funds stay in the account which backs deposits and the total issuance is
+	/// unchanged.
+	fn checks_teleports() -> bool {
+		Config::DEPOSIT_STRATEGY == DepositStrategy::Mint
+	}
+
+	/// The account of the reserve location of `what`, which backs its deposits under
+	/// `TransferFromReserveAccount`.
+	fn reserve_account(what: &MultiAsset) -> result::Result<AccountId, CurrencyAdapterError> {
+		let reserve =
+			Config::reserve_location(what).ok_or(CurrencyAdapterError::NoReserveLocation)?;
+		Self::account(&reserve)
+	}
+
+	/// Move the shortfall of the free balance of `who` below `amount` out of its reserved balance
+	/// with `Config::unreserve`, if the reserved balance can cover it.
+	fn unreserve_shortfall(who: &AccountId, amount: Currency::Balance) {
+		let free = Currency::free_balance(who);
+		if free < amount && Currency::total_balance(who) >= amount {
+			Config::unreserve(who, amount - free);
+		}
+	}
+
+	/// The part of the free balance `free` of `who` which withdrawing `amount` would leave below
+	/// the minimum balance, and which is withdrawn along with it under `DustHandling::Sweep`. Fails
+	/// with `BelowMinimumBalance` under `DustHandling::Forbid` instead.
+	fn dust(
+		who: &AccountId,
+		amount: Currency::Balance,
+		free: Currency::Balance,
+	) -> result::Result<Currency::Balance, CurrencyAdapterError> {
+		if Config::DUST_HANDLING == DustHandling::Reap {
+			return Ok(Zero::zero())
+		}
+		let remaining = Currency::total_balance(who).saturating_sub(amount);
+		if remaining.is_zero() || remaining >= Currency::minimum_balance() {
+			Ok(Zero::zero())
+		} else if Config::DUST_HANDLING == DustHandling::Forbid {
+			Err(CurrencyAdapterError::BelowMinimumBalance)
+		} else {
+			Ok(free.saturating_sub(amount))
+		}
+	}
+
+	/// Whether `transfer_asset` can move funds with a single `Currency::transfer`, which is the case
+	/// unless reserved funds or swept dust have to be withdrawn along with them. The deposit
+	/// strategy does not matter, as the funds of a transfer always come from the sender; otherwise
+	/// they are burnt and minted into the receiver.
+	fn transfers_directly() -> bool {
+		!Config::INCLUDE_RESERVED && Config::DUST_HANDLING != DustHandling::Sweep
+	}
+
+	/// Transfer `what` with `Currency::transfer`, matching it and resolving both accounts once, so
+	/// that currencies with transfer hooks see a single atomic move. The withdraw reasons and
+	/// forbidden dust are checked up front. No imbalance is created, so `on_unbalanced` is not
+	/// called.
+	fn transfer_directly(
+		what: &MultiAsset,
+		from: &MultiLocation,
+		to: &MultiLocation,
+	) -> result::Result<Assets, XcmError> {
+		let failed_from = |e| Self::failed("transfer_asset", what, from, e);
+		let amount = Self::matched_amount(what).map_err(failed_from)?;
+		let source = Self::account(from).map_err(failed_from)?;
+		let dest = Self::account(to).map_err(|e| Self::failed("transfer_asset", what, to, e))?;
+		let new_balance = Currency::free_balance(&source)
+			.checked_sub(&amount)
+			.ok_or(CurrencyAdapterError::InsufficientBalance)
+			.map_err(failed_from)?;
+		Currency::ensure_can_withdraw(&source, amount, Config::WITHDRAW_REASONS, new_balance)
+			.map_err(|e| failed_from(e.into()))?;
+		if Config::DUST_HANDLING == DustHandling::Forbid {
+			let remaining = Currency::total_balance(&source).saturating_sub(amount);
+			if !remaining.is_zero() && remaining < Currency::minimum_balance() {
+				return Err(failed_from(CurrencyAdapterError::BelowMinimumBalance))
+			}
+		}
+		let xcm_amount = Self::xcm_amount(what, amount).map_err(failed_from)?;
+		Currency::transfer(&source, &dest, amount, AllowDeath)
+			.map_err(|e| failed_from(e.into()))?;
+		log::debug!(target: "xcm::currency_adapter", "transfer_asset succeeded: from: {:?}, to: {:?}, amount: {:?}", source, dest, amount);
+		Config::on_withdraw(&source, what, amount);
+		Config::on_deposit(&dest, what, amount);
+		Ok(MultiAsset { id: what.id.clone(), fun: Fungible(xcm_amount) }.into())
+	}
+
+	/// Whether `e` is to be ignored under [`UnknownAssetPolicy::Ignore`].
+	fn ignored(e: &CurrencyAdapterError) -> bool {
+		Config::UNKNOWN_ASSET_POLICY == UnknownAssetPolicy::Ignore &&
+			matches!(e, CurrencyAdapterError::AssetNotFound(_))
+	}
+
+	/// Log a failed operation with the context of its error, and report it to `Config::on_failure`
+	/// unless the asset was simply not ours.
+	fn failed(
+		operation: &str,
+		what: &MultiAsset,
+		who: &MultiLocation,
+		e: CurrencyAdapterError,
+	) -> XcmError {
+		log::debug!(target: "xcm::currency_adapter", "{} failed: who: {:?}, error: {:?}", operation, who, e);
+		let not_found = matches!(e, CurrencyAdapterError::AssetNotFound(_));
+		let e = XcmError::from(e);
+		if !not_found {
+			Config::on_failure(what, who, &e);
+		}
+		e
+	}
+
+	/// Deposit `what` into `who`, funded as `strategy` says.
+	fn do_deposit_asset(
+		what: &MultiAsset,
+		who: &MultiLocation,
+		strategy: DepositStrategy,
+	) -> result::Result<(), CurrencyAdapterError> {
+		// Check we handle this asset.
+		let balance_amount = Self::matched_amount(what)?;
+		let who = Self::account(who)?;
+		// `deposit_creating` silently drops amounts which cannot create the account.
+		if Currency::total_balance(&who).is_zero() && balance_amount < Currency::minimum_balance() {
+			return Err(CurrencyAdapterError::BelowMinimumBalance)
+		}
+		#[cfg(debug_assertions)]
+		let before = (Currency::total_issuance(), Currency::free_balance(&who));
+		#[cfg_attr(not(debug_assertions), allow(unused_variables))]
+		let minted = match strategy {
+			DepositStrategy::Mint => {
+				// The imbalance is dropped immediately, which updates the total issuance.
+				let _ = Currency::deposit_creating(&who, balance_amount);
+				balance_amount
+			},
+			DepositStrategy::TransferFromCheckingAccount => {
+				let checked_account =
+					Self::checking_account(what).ok_or(CurrencyAdapterError::NoCheckingAccount)?;
+				Currency::transfer(
+					&checked_account,
+					&who,
+					balance_amount,
+					Config::CHECKING_ACCOUNT_EXISTENCE,
+				)?;
+				Zero::zero()
+			},
+			DepositStrategy::TransferFromReserveAccount => {
+				Currency::transfer(&Self::reserve_account(what)?, &who, balance_amount, KeepAlive)?;
+				Zero::zero()
+			},
+		};
+		#[cfg(debug_assertions)]
+		{
+			debug_assert_eq!(
+				Currency::total_issuance(),
+				before.0 + minted,
+				"deposit must increase the total issuance by the amount minted",
+			);
+			// A transfer may come from `who` itself, e.g. a deposit into the checking account under
+			// `TransferFromCheckingAccount`, leaving its balance unchanged, so only minted deposits
+			// are checked to credit the account.
+			if strategy == DepositStrategy::Mint {
+				debug_assert_eq!(
+					Currency::free_balance(&who),
+					before.1 + balance_amount,
+					"deposit must credit the account with the amount deposited",
+				);
+			}
+		}
+		log::debug!(target: "xcm::currency_adapter", "deposit_asset succeeded: who: {:?}, amount: {:?}", who, balance_amount);
+		Config::on_deposit(&who, what, balance_amount);
+		Ok(())
+	}
+
+	/// Withdraw `what` from `who`, crediting the funds as `strategy` says.
+	fn do_withdraw_asset(
+		what: &MultiAsset,
+		who: &MultiLocation,
+		rounding: Rounding,
+		strategy: DepositStrategy,
+	) -> result::Result<Assets, CurrencyAdapterError> {
+		// Check we handle this asset.
+		let balance_amount = Self::matched_amount_rounded(what, rounding)?;
+		Self::withdraw_from(what, balance_amount, &Self::account(who)?, strategy)
+	}
+
+	/// Withdraw `balance_amount`, matched from `what`, from the account `who`.
+	///
+	/// Under `INCLUDE_RESERVED` this runs in a storage transaction, so that funds unreserved for a
+	/// withdrawal which then fails, e.g. because of a lock, are reserved again.
+	fn withdraw_from(
+		what: &MultiAsset,
+		balance_amount: Currency::Balance,
+		who: &AccountId,
+		strategy: DepositStrategy,
+	) -> result::Result<Assets, CurrencyAdapterError> {
+		if !Config::INCLUDE_RESERVED {
+			return Self::withdraw_free(what, balance_amount, who, strategy)
+		}
+		frame_support::storage::with_transaction(|| {
+			Self::unreserve_shortfall(who, balance_amount);
+			match Self::withdraw_free(what, balance_amount, who, strategy) {
+				Ok(assets) => TransactionOutcome::Commit(Ok(assets)),
+				Err(e) => TransactionOutcome::Rollback(Err(e)),
+			}
+		})
+	}
+
+	/// Withdraw `balance_amount`, matched from `what`, from the free balance of `who`.
+	fn withdraw_free(
+		what: &MultiAsset,
+		balance_amount: Currency::Balance,
+		who: &AccountId,
+		strategy: DepositStrategy,
+	) -> result::Result<Assets, CurrencyAdapterError> {
+		let credited = Self::credited_account(what, balance_amount, strategy)?;
+		let dust = Self::dust(who, balance_amount, Currency::free_balance(who))?;
+		// Converted up front, so that an amount which cannot be reported fails before withdrawing.
+		let xcm_amount = Self::xcm_amount(what, balance_amount)?;
+		#[cfg(debug_assertions)]
+		let before = Currency::free_balance(who);
+		let requested = balance_amount + dust;
+		let imbalance = Currency::withdraw(who, requested, Config::WITHDRAW_REASONS, AllowDeath)
+			.map_err(CurrencyAdapterError::from)?;
+		#[cfg(debug_assertions)]
+		{
+			debug_assert_eq!(
+				imbalance.peek(),
+				requested,
+				"withdrawal must produce an imbalance of the amount withdrawn",
+			);
+			// More than the amount may leave the account if the remainder is reaped as dust, which
+			// the currency may burn or credit elsewhere, so the total issuance is not checked.
+			debug_assert!(
+				before - Currency::free_balance(who) >= requested,
+				"withdrawal must debit the account by at least the amount withdrawn",
+			);
+		}
+		let (imbalance, swept) = imbalance.split(balance_amount);
+		if !swept.peek().is_zero() {
+			Config::on_dust(swept);
+		}
+		log::debug!(target: "xcm::currency_adapter", "withdraw_asset succeeded: who: {:?}, amount: {:?}", who, balance_amount);
+		Config::on_unbalanced(&imbalance);
+		match credited {
+			Some(account) => Currency::resolve_creating(&account, imbalance),
+			// Dropping the imbalance burns the withdrawn funds.
+			None => drop(imbalance),
+		}
+		Config::on_withdraw(who, what, balance_amount);
+		Ok(MultiAsset { id: what.id.clone(), fun: Fungible(xcm_amount) }.into())
+	}
+}
+
+impl<
+		Matcher: MatchesFungible<Currency::Balance>,
+		AccountIdConverter: Convert<MultiLocation, AccountId>,
+		Currency: frame_support::traits::Currency<AccountId>,
+		AccountId: Clone + Debug, // can't get away without it since Currency is generic over it.
+		CheckedAccount: Get<Option<AccountId>>,
+		Config: CurrencyAdapterConfig<AccountId, Currency>,
 	> TransactAsset
-	for CurrencyAdapter<Currency, Matcher, AccountIdConverter, AccountId, CheckedAccount>
+	for CurrencyAdapter<Currency, Matcher, AccountIdConverter, AccountId, CheckedAccount, Config>
 {
 	fn can_check_in(_origin: &MultiLocation, what: &MultiAsset) -> Result {
 		log::trace!(target: "xcm::currency_adapter", "can_check_in origin: {:?}, what: {:?}", _origin, what);
-		// Check we handle this asset.
-		let amount: Currency::Balance =
-			Matcher::matches_fungible(what).ok_or(Error::AssetNotFound)?;
-		if let Some(checked_account) = CheckedAccount::get() {
-			let new_balance = Currency::free_balance(&checked_account)
+		// Check we handle this asset before anything else, so that other transactors get a chance.
+		let amount = Self::matched_amount(what)?;
+		let checked_account = Self::checking_account(what);
+		if checked_account.is_none() && Config::REQUIRE_CHECKING_ACCOUNT {
+			return Err(CurrencyAdapterError::NoCheckingAccount.into())
+		}
+		if let Some(checked_account) = checked_account {
+			let available = Currency::free_balance(&checked_account);
+			let not_withdrawable = |reason: &str| {
+				log::trace!(
+					target: "xcm::currency_adapter",
+					"can_check_in not withdrawable: {}: required: {:?}, available: {:?}",
+					reason,
+					amount,
+					available,
+				);
+				XcmError::NotWithdrawable
+			};
+			let new_balance = available
 				.checked_sub(&amount)
-				.ok_or(XcmError::NotWithdrawable)?;
+				.ok_or_else(|| not_withdrawable("insufficient balance"))?;
+			if Config::CHECKING_ACCOUNT_EXISTENCE == KeepAlive &&
+				new_balance < Currency::minimum_balance()
+			{
+				return Err(not_withdrawable("checking account would be reaped"))
+			}
 			Currency::ensure_can_withdraw(
 				&checked_account,
 				amount,
-				WithdrawReasons::TRANSFER,
+				Config::WITHDRAW_REASONS,
 				new_balance,
 			)
-			.map_err(|_| XcmError::NotWithdrawable)?;
+			.map_err(|_| not_withdrawable("balance locked"))?;
 		}
 		Ok(())
 	}
 
 	fn check_in(_origin: &MultiLocation, what: &MultiAsset) {
 		log::trace!(target: "xcm::currency_adapter", "check_in origin: {:?}, what: {:?}", _origin, what);
-		if let Some(amount) = Matcher::matches_fungible(what) {
-			if let Some(checked_account) = CheckedAccount::get() {
+		if !Self::checks_teleports() {
+			return
+		}
+		// Zero amounts are not checked in.
+		if let Ok(amount) = Self::matched_amount(what) {
+			if let Some(checked_account) = Self::checking_account(what) {
 				let ok = Currency::withdraw(
 					&checked_account,
 					amount,
-					WithdrawReasons::TRANSFER,
-					AllowDeath,
+					Config::WITHDRAW_REASONS,
+					Config::CHECKING_ACCOUNT_EXISTENCE,
 				)
 				.is_ok();
 				debug_assert!(
 					ok,
 					"`can_check_in` must have returned `true` immediately prior; qed"
 				);
+			} else if Config::REQUIRE_CHECKING_ACCOUNT {
+				log::error!(target: "xcm::currency_adapter", "check_in without checking account: {:?}", what);
 			}
 		}
 	}
 
+	fn can_check_out(_dest: &MultiLocation, what: &MultiAsset) -> Result {
+		log::trace!(target: "xcm::currency_adapter", "can_check_out dest: {:?}, what: {:?}", _dest, what);
+		// Assets we don't handle need no bookkeeping here, but ours are rejected as by
+		// `can_check_in` if they cannot be checked out, e.g. because their amount is zero.
+		let amount = match Self::matched_amount(what) {
+			Ok(amount) => amount,
+			Err(CurrencyAdapterError::AssetNotFound(_)) => return Ok(()),
+			Err(e) => return Err(e.into()),
+		};
+		let checked_account = Self::checking_account(what);
+		if checked_account.is_none() && Config::REQUIRE_CHECKING_ACCOUNT {
+			return Err(CurrencyAdapterError::NoCheckingAccount.into())
+		}
+		if let Some(checked_account) = checked_account.filter(|_| Self::checks_teleports()) {
+			// `deposit_creating` silently drops amounts which cannot create the account.
+			if Currency::total_balance(&checked_account).is_zero() &&
+				amount < Currency::minimum_balance() &&
+				!Config::FUND_CHECKING_ACCOUNT
+			{
+				return Err(CurrencyAdapterError::BelowMinimumBalance.into())
+			}
+			Currency::total_issuance()
+				.checked_add(
+					&amount.saturating_add(Self::checking_account_top_up(&checked_account)),
+				)
+				.ok_or(CurrencyAdapterError::IssuanceOverflow)?;
+		}
+		Ok(())
+	}
+
 	fn check_out(_dest: &MultiLocation, what: &MultiAsset) {
 		log::trace!(target: "xcm::currency_adapter", "check_out dest: {:?}, what: {:?}", _dest, what);
-		if let Some(amount) = Matcher::matches_fungible(what) {
-			if let Some(checked_account) = CheckedAccount::get() {
-				Currency::deposit_creating(&checked_account, amount);
+		if !Self::checks_teleports() {
+			return
+		}
+		if let Ok(amount) = Self::matched_amount(what) {
+			if let Some(checked_account) = Self::checking_account(what) {
+				#[cfg(debug_assertions)]
+				let before = Currency::free_balance(&checked_account);
+				let minted = amount.saturating_add(Self::checking_account_top_up(&checked_account));
+				let _ = Currency::deposit_creating(&checked_account, minted);
+				#[cfg(debug_assertions)]
+				debug_assert_eq!(
+					Currency::free_balance(&checked_account),
+					before + minted,
+					"`can_check_out` must have returned `true` immediately prior; qed"
+				);
+			} else if Config::REQUIRE_CHECKING_ACCOUNT {
+				log::error!(target: "xcm::currency_adapter", "check_out without checking account: {:?}", what);
 			}
 		}
 	}
 
 	fn deposit_asset(what: &MultiAsset, who: &MultiLocation) -> Result {
 		log::trace!(target: "xcm::currency_adapter", "deposit_asset what: {:?}, who: {:?}", what, who);
-		// Check we handle this asset.
-		let amount: u128 =
-			Matcher::matches_fungible(&what).ok_or(Error::AssetNotFound)?.saturated_into();
-		let who =
-			AccountIdConverter::convert_ref(who).map_err(|()| Error::AccountIdConversionFailed)?;
-		let balance_amount =
-			amount.try_into().map_err(|_| Error::AmountToBalanceConversionFailed)?;
-		let _imbalance = Currency::deposit_creating(&who, balance_amount);
-		Ok(())
+		match Self::do_deposit_asset(what, who, Config::DEPOSIT_STRATEGY) {
+			Err(e) if Self::ignored(&e) => {
+				log::debug!(target: "xcm::currency_adapter", "deposit_asset ignored unknown asset: {:?}", what);
+				Ok(())
+			},
+			r => r.map_err(|e| Self::failed("deposit_asset", what, who, e)),
+		}
 	}
 
 	fn withdraw_asset(what: &MultiAsset, who: &MultiLocation) -> result::Result<Assets, XcmError> {
 		log::trace!(target: "xcm::currency_adapter", "withdraw_asset what: {:?}, who: {:?}", what, who);
+		match Self::do_withdraw_asset(
+			what,
+			who,
+			Config::WITHDRAW_ROUNDING,
+			Config::DEPOSIT_STRATEGY,
+		) {
+			Err(e) if Self::ignored(&e) => {
+				log::debug!(target: "xcm::currency_adapter", "withdraw_asset ignored unknown asset: {:?}", what);
+				Ok(Assets::new())
+			},
+			r => r.map_err(|e| Self::failed("withdraw_asset", what, who, e)),
+		}
+	}
+
+	fn transfer_asset(
+		what: &MultiAsset,
+		from: &MultiLocation,
+		to: &MultiLocation,
+	) -> result::Result<Assets, XcmError> {
+		log::trace!(target: "xcm::currency_adapter", "transfer_asset what: {:?}, from: {:?}, to: {:?}", what, from, to);
+		if Self::transfers_directly() {
+			return Self::transfer_directly(what, from, to)
+		}
+		// Withdraw and deposit in one storage transaction, so that a failed deposit does not leave
+		// the funds withdrawn from `from`. Both round down, so nothing is lost in between. The
+		// withdrawal burns the funds and the deposit mints them whatever the deposit strategy, so
+		// that the checking or reserve account is not involved.
+		frame_support::storage::with_transaction(|| {
+			let assets =
+				match Self::do_withdraw_asset(what, from, Rounding::Down, DepositStrategy::Mint) {
+					Ok(assets) => assets,
+					Err(e) => return TransactionOutcome::Rollback(Err((from, e))),
+				};
+			match Self::do_deposit_asset(what, to, DepositStrategy::Mint) {
+				Ok(()) => TransactionOutcome::Commit(Ok(assets)),
+				Err(e) => TransactionOutcome::Rollback(Err((to, e))),
+			}
+		})
+		.map_err(|(who, e)| Self::failed("transfer_asset", what, who, e))
+	}
+}
+
+/// Variant of [`CurrencyAdapter`] which does not track teleports through a checking account.
+///
+/// Assets teleported out are burnt by the withdrawal from the sender and assets teleported in are
+/// minted by the deposit into the beneficiary, so the total issuance always reflects the teleports.
+/// `can_check_in` only ensures that the total issuance can absorb the incoming amount.
+pub struct BurnCurrencyAdapter<Currency, Matcher, AccountIdConverter, AccountId, Config = ()>(
+	PhantomData<(Currency, Matcher, AccountIdConverter, AccountId, Config)>,
+);
+
+impl<
+		Matcher: MatchesFungible<Currency::Balance>,
+		AccountIdConverter: Convert<MultiLocation, AccountId>,
+		Currency: frame_support::traits::Currency<AccountId>,
+		AccountId: Clone + Debug, // can't get away without it since Currency is generic over it.
+		Config: CurrencyAdapterConfig<AccountId, Currency>,
+	> TransactAsset for BurnCurrencyAdapter<Currency, Matcher, AccountIdConverter, AccountId, Config>
+{
+	fn can_check_in(_origin: &MultiLocation, what: &MultiAsset) -> Result {
+		log::trace!(target: "xcm::currency_adapter", "can_check_in origin: {:?}, what: {:?}", _origin, what);
 		// Check we handle this asset.
-		let amount: u128 =
-			Matcher::matches_fungible(what).ok_or(Error::AssetNotFound)?.saturated_into();
+		let amount =
+			CurrencyAdapter::<Currency, Matcher, AccountIdConverter, AccountId, (), Config>::matched_amount(
+				what,
+			)?;
+		Currency::total_issuance()
+			.checked_add(&amount)
+			.ok_or(CurrencyAdapterError::IssuanceOverflow)?;
+		Ok(())
+	}
+
+	fn check_in(_origin: &MultiLocation, _what: &MultiAsset) {
+		// The subsequent deposit mints the teleported amount.
+	}
+
+	fn check_out(_dest: &MultiLocation, _what: &MultiAsset) {
+		// The preceding withdrawal has already burnt the teleported amount.
+	}
+
+	fn deposit_asset(what: &MultiAsset, who: &MultiLocation) -> Result {
+		CurrencyAdapter::<Currency, Matcher, AccountIdConverter, AccountId, (), Config>::deposit_asset(
+			what, who,
+		)
+	}
+
+	fn withdraw_asset(what: &MultiAsset, who: &MultiLocation) -> result::Result<Assets, XcmError> {
+		CurrencyAdapter::<Currency, Matcher, AccountIdConverter, AccountId, (), Config>::withdraw_asset(
+			what, who,
+		)
+	}
+
+	fn transfer_asset(
+		what: &MultiAsset,
+		from: &MultiLocation,
+		to: &MultiLocation,
+	) -> result::Result<Assets, XcmError> {
+		CurrencyAdapter::<Currency, Matcher, AccountIdConverter, AccountId, (), Config>::transfer_asset(
+			what, from, to,
+		)
+	}
+}
+
+/// The amount of a balance lock, which `LockableCurrency` does not expose. For `pallet-balances`
+/// this is the `amount` of the entry with the given identifier in `Pallet::locks`, or zero.
+pub trait InspectLock<AccountId, Balance> {
+	/// The amount locked under `id` on the account `who`.
+	fn locked(id: LockIdentifier, who: &AccountId) -> Balance;
+}
+
+/// Variant of [`CurrencyAdapter`] which locks assets in place instead of moving them, e.g. for
+/// bridges which lock funds on one side rather than burning them.
+///
+/// `withdraw_asset` adds the amount to the lock `LockId` on the account of `who`, whose current
+/// amount `Locks` reads, and `deposit_asset` takes it off again. Only funds which no other lock
+/// holds can be locked, and a deposit fails if the account has less than its amount locked, as
+/// nothing would back it. Teleports in are not supported, and transfers fail with
+/// `TransferNotSupported`, as they would move no funds.
+pub struct LockingCurrencyAdapter<Currency, Matcher, AccountIdConverter, AccountId, LockId, Locks>(
+	PhantomData<(Currency, Matcher, AccountIdConverter, AccountId, LockId, Locks)>,
+);
+
+impl<
+		Matcher: MatchesFungible<Currency::Balance>,
+		AccountIdConverter: Convert<MultiLocation, AccountId>,
+		Currency: LockableCurrency<AccountId>,
+		AccountId: Clone + Debug, // can't get away without it since Currency is generic over it.
+		LockId: Get<LockIdentifier>,
+		Locks: InspectLock<AccountId, Currency::Balance>,
+	> LockingCurrencyAdapter<Currency, Matcher, AccountIdConverter, AccountId, LockId, Locks>
+{
+	/// The amount of `what`, and the account of `who`.
+	fn amount_and_account(
+		what: &MultiAsset,
+		who: &MultiLocation,
+	) -> result::Result<(Currency::Balance, AccountId), CurrencyAdapterError> {
+		let amount =
+			CurrencyAdapter::<Currency, Matcher, AccountIdConverter, AccountId, ()>::matched_amount(
+				what,
+			)?;
 		let who =
-			AccountIdConverter::convert_ref(who).map_err(|()| Error::AccountIdConversionFailed)?;
-		let balance_amount =
-			amount.try_into().map_err(|_| Error::AmountToBalanceConversionFailed)?;
-		Currency::withdraw(&who, balance_amount, WithdrawReasons::TRANSFER, AllowDeath)
-			.map_err(|e| XcmError::FailedToTransactAsset(e.into()))?;
+			CurrencyAdapter::<Currency, Matcher, AccountIdConverter, AccountId, ()>::account(who)?;
+		Ok((amount, who))
+	}
+
+	/// Set the lock `LockId` on `who` to `amount`, removing it if that is zero.
+	fn set_locked(who: &AccountId, amount: Currency::Balance) {
+		if amount.is_zero() {
+			Currency::remove_lock(LockId::get(), who);
+		} else {
+			Currency::set_lock(LockId::get(), who, amount, WithdrawReasons::all());
+		}
+	}
+}
+
+impl<
+		Matcher: MatchesFungible<Currency::Balance>,
+		AccountIdConverter: Convert<MultiLocation, AccountId>,
+		Currency: LockableCurrency<AccountId>,
+		AccountId: Clone + Debug, // can't get away without it since Currency is generic over it.
+		LockId: Get<LockIdentifier>,
+		Locks: InspectLock<AccountId, Currency::Balance>,
+	> TransactAsset
+	for LockingCurrencyAdapter<Currency, Matcher, AccountIdConverter, AccountId, LockId, Locks>
+{
+	fn deposit_asset(what: &MultiAsset, who: &MultiLocation) -> Result {
+		log::trace!(target: "xcm::currency_adapter", "unlock deposit_asset what: {:?}, who: {:?}", what, who);
+		let (amount, who) = Self::amount_and_account(what, who)?;
+		let locked = Locks::locked(LockId::get(), &who)
+			.checked_sub(&amount)
+			.ok_or(CurrencyAdapterError::NotLocked)?;
+		Self::set_locked(&who, locked);
+		Ok(())
+	}
+
+	fn withdraw_asset(what: &MultiAsset, who: &MultiLocation) -> result::Result<Assets, XcmError> {
+		log::trace!(target: "xcm::currency_adapter", "lock withdraw_asset what: {:?}, who: {:?}", what, who);
+		let (amount, who) = Self::amount_and_account(what, who)?;
+		// Locks overlap rather than add up, so the amount must be spendable despite all locks,
+		// including the one of this adapter, to be backed by funds no other lock holds.
+		let new_balance = Currency::free_balance(&who)
+			.checked_sub(&amount)
+			.ok_or(CurrencyAdapterError::InsufficientBalance)?;
+		Currency::ensure_can_withdraw(&who, amount, WithdrawReasons::all(), new_balance)
+			.map_err(CurrencyAdapterError::from)?;
+		Self::set_locked(&who, Locks::locked(LockId::get(), &who).saturating_add(amount));
 		Ok(what.clone().into())
 	}
+
+	fn transfer_asset(
+		what: &MultiAsset,
+		from: &MultiLocation,
+		_to: &MultiLocation,
+	) -> result::Result<Assets, XcmError> {
+		// Fail with an error other than `Unimplemented`, so that `beam_asset` does not fall back to
+		// locking the funds of `from` and unlocking those of `to`.
+		Self::amount_and_account(what, from)?;
+		Err(CurrencyAdapterError::TransferNotSupported.into())
+	}
+}
+
+/// Wrapper around the asset transactor `Inner` which only transacts with the locations `Allowed`
+/// contains, failing with `FailedToTransactAsset("LocationNotAllowed")` otherwise, even for assets
+/// `Inner` would accept.
+///
+/// Assets `Matcher` does not match fail with `AssetNotFound` whatever the location, so that in a
+/// tuple of transactors they are still offered to the next one. `Matcher` should be the one of
+/// `Inner`.
+///
+/// `check_in` and `check_out` cannot fail, so they are delegated as they are; teleports from or to
+/// other locations are stopped by `can_check_in` and `can_check_out`, which precede them.
+pub struct AllowedLocations<Inner, Matcher, Allowed>(PhantomData<(Inner, Matcher, Allowed)>);
+
+impl<Inner, Matcher, Allowed> AllowedLocations<Inner, Matcher, Allowed>
+where
+	Matcher: MatchesFungible<u128>,
+	Allowed: Contains<MultiLocation>,
+{
+	fn ensure_allowed(operation: &str, what: &MultiAsset, location: &MultiLocation) -> Result {
+		if Matcher::matches_fungible(what).is_none() {
+			Err(XcmError::AssetNotFound)
+		} else if Allowed::contains(location) {
+			Ok(())
+		} else {
+			log::debug!(target: "xcm::currency_adapter", "{} location not allowed: {:?}", operation, location);
+			Err(XcmError::FailedToTransactAsset("LocationNotAllowed"))
+		}
+	}
+}
+
+impl<Inner: TransactAsset, Matcher: MatchesFungible<u128>, Allowed: Contains<MultiLocation>>
+	TransactAsset for AllowedLocations<Inner, Matcher, Allowed>
+{
+	fn can_check_in(origin: &MultiLocation, what: &MultiAsset) -> Result {
+		Self::ensure_allowed("can_check_in", what, origin)?;
+		Inner::can_check_in(origin, what)
+	}
+
+	fn check_in(origin: &MultiLocation, what: &MultiAsset) {
+		Inner::check_in(origin, what)
+	}
+
+	fn can_check_out(dest: &MultiLocation, what: &MultiAsset) -> Result {
+		Self::ensure_allowed("can_check_out", what, dest)?;
+		Inner::can_check_out(dest, what)
+	}
+
+	fn check_out(dest: &MultiLocation, what: &MultiAsset) {
+		Inner::check_out(dest, what)
+	}
+
+	fn deposit_asset(what: &MultiAsset, who: &MultiLocation) -> Result {
+		Self::ensure_allowed("deposit_asset", what, who)?;
+		Inner::deposit_asset(what, who)
+	}
+
+	fn withdraw_asset(what: &MultiAsset, who: &MultiLocation) -> result::Result<Assets, XcmError> {
+		Self::ensure_allowed("withdraw_asset", what, who)?;
+		Inner::withdraw_asset(what, who)
+	}
+
+	fn transfer_asset(
+		what: &MultiAsset,
+		from: &MultiLocation,
+		to: &MultiLocation,
+	) -> result::Result<Assets, XcmError> {
+		Self::ensure_allowed("transfer_asset", what, from)?;
+		Self::ensure_allowed("transfer_asset", what, to)?;
+		Inner::transfer_asset(what, from, to)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn amount_conversion_rejects_or_saturates() {
+		let huge = u64::MAX as u128 + 1;
+		assert_eq!(AmountConversion::Reject.convert::<u64>(huge), None);
+		assert_eq!(AmountConversion::Saturate.convert::<u64>(huge), Some(u64::MAX));
+		assert_eq!(AmountConversion::Reject.convert::<u64>(42), Some(42));
+		assert_eq!(AmountConversion::Saturate.convert::<u64>(42), Some(42));
+	}
+
+	#[test]
+	fn balances_wider_than_u128_are_rejected_or_saturated() {
+		use sp_core::U256;
+		let huge = U256::from(u128::MAX) + 1;
+		assert_eq!(AmountConversion::Reject.balance_to_u128(huge), None);
+		assert_eq!(AmountConversion::Saturate.balance_to_u128(huge), Some(u128::MAX));
+		assert_eq!(AmountConversion::Reject.balance_to_u128(U256::from(42)), Some(42));
+		assert_eq!(AmountConversion::Saturate.balance_to_u128(U256::from(42)), Some(42));
+	}
 }
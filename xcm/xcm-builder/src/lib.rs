@@ -49,7 +49,11 @@ pub use barriers::{
 };
 
 mod currency_adapter;
-pub use currency_adapter::CurrencyAdapter;
+pub use currency_adapter::{
+	decimals_rate, AllowedLocations, AmountConversion, BurnCurrencyAdapter, CheckingAccountDelta,
+	CurrencyAdapter, CurrencyAdapterConfig, DepositStrategy, DustHandling, InspectLock,
+	LockingCurrencyAdapter, Rounding, UnknownAssetPolicy,
+};
 
 mod fungibles_adapter;
 pub use fungibles_adapter::{
@@ -57,6 +61,9 @@ pub use fungibles_adapter::{
 	FungiblesMutateAdapter, FungiblesTransferAdapter,
 };
 
+mod nonfungible_adapter;
+pub use nonfungible_adapter::NonFungibleAdapter;
+
 mod weight;
 #[allow(deprecated)]
 pub use weight::FixedRateOfConcreteFungible;
@@ -65,7 +72,7 @@ pub use weight::{
 };
 
 mod matches_fungible;
-pub use matches_fungible::{IsAbstract, IsConcrete};
+pub use matches_fungible::{AssetRegistry, EitherMatcher, IsAbstract, IsConcrete, IsRegistered};
 
 mod filter_asset_location;
 pub use filter_asset_location::{Case, NativeAsset};
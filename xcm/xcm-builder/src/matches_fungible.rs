@@ -14,17 +14,21 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Various implementations for the `MatchesFungible` trait.
+//! Various implementations for the `MatchesFungible` and `MatchesNonFungible` traits.
 
 use frame_support::traits::Get;
 use sp_runtime::traits::CheckedConversion;
-use sp_std::{convert::TryFrom, marker::PhantomData};
+use sp_std::{
+	convert::{TryFrom, TryInto},
+	marker::PhantomData,
+};
 use xcm::latest::{
 	AssetId::{Abstract, Concrete},
-	Fungibility::Fungible,
+	AssetInstance,
+	Fungibility::{Fungible, NonFungible},
 	MultiAsset, MultiLocation,
 };
-use xcm_executor::traits::MatchesFungible;
+use xcm_executor::traits::{MatchesFungible, MatchesNonFungible};
 
 /// Converts a `MultiAsset` into balance `B` if it is a concrete fungible with an id equal to that
 /// given by `T`'s `Get`.
@@ -57,6 +61,16 @@ impl<T: Get<MultiLocation>, B: TryFrom<u128>> MatchesFungible<B> for IsConcrete<
 	}
 }
 
+impl<T: Get<MultiLocation>, I: TryFrom<AssetInstance>> MatchesNonFungible<I> for IsConcrete<T> {
+	fn matches_nonfungible(a: &MultiAsset) -> Option<I> {
+		match (&a.id, &a.fun) {
+			(Concrete(ref id), NonFungible(ref instance)) if id == &T::get() =>
+				instance.clone().try_into().ok(),
+			_ => None,
+		}
+	}
+}
+
 /// Same as [`IsConcrete`] but for a fungible with abstract location.
 ///
 /// # Example
@@ -86,3 +100,87 @@ impl<T: Get<&'static [u8]>, B: TryFrom<u128>> MatchesFungible<B> for IsAbstract<
 		}
 	}
 }
+
+impl<T: Get<&'static [u8]>, I: TryFrom<AssetInstance>> MatchesNonFungible<I> for IsAbstract<T> {
+	fn matches_nonfungible(a: &MultiAsset) -> Option<I> {
+		match (&a.id, &a.fun) {
+			(Abstract(ref id), NonFungible(ref instance)) if id == &T::get() =>
+				instance.clone().try_into().ok(),
+			_ => None,
+		}
+	}
+}
+
+/// Matches an asset with `A`, falling back to `B` if `A` does not match it.
+///
+/// This allows e.g. a single [`CurrencyAdapter`](crate::CurrencyAdapter) to accept a currency under
+/// both its concrete and abstract identifiers while migrating from one to the other.
+///
+/// # Example
+///
+/// ```
+/// use xcm::latest::prelude::*;
+/// use xcm_builder::{EitherMatcher, IsAbstract, IsConcrete};
+/// use xcm_executor::traits::MatchesFungible;
+///
+/// frame_support::parameter_types! {
+/// 	pub RelayLocation: MultiLocation = Parent.into();
+/// 	pub RelayName: &'static [u8] = &[7u8];
+/// }
+///
+/// type Matcher = EitherMatcher<IsConcrete<RelayLocation>, IsAbstract<RelayName>>;
+///
+/// # fn main() {
+/// assert_eq!(<Matcher as MatchesFungible<u128>>::matches_fungible(&(Parent, 999).into()), Some(999));
+/// assert_eq!(<Matcher as MatchesFungible<u128>>::matches_fungible(&(vec![7u8], 999).into()), Some(999));
+/// assert_eq!(<Matcher as MatchesFungible<u128>>::matches_fungible(&(Here, 999).into()), None);
+/// # }
+/// ```
+pub struct EitherMatcher<A, B>(PhantomData<(A, B)>);
+impl<A: MatchesFungible<Balance>, B: MatchesFungible<Balance>, Balance> MatchesFungible<Balance>
+	for EitherMatcher<A, B>
+{
+	fn matches_fungible(a: &MultiAsset) -> Option<Balance> {
+		A::matches_fungible(a).or_else(|| B::matches_fungible(a))
+	}
+}
+
+/// A runtime registry of the asset locations which represent a currency, e.g. kept in storage and
+/// managed by governance.
+pub trait AssetRegistry {
+	/// Whether the asset at `location` is registered.
+	fn is_registered(location: &MultiLocation) -> bool;
+}
+
+/// Converts a `MultiAsset` into balance `B` if it is a concrete fungible whose location is
+/// registered in `R`, so that new assets can be accepted without a runtime upgrade.
+///
+/// # Example
+///
+/// ```
+/// use xcm::latest::prelude::*;
+/// use xcm_builder::{AssetRegistry, IsRegistered};
+/// use xcm_executor::traits::MatchesFungible;
+///
+/// struct ParentOnly;
+/// impl AssetRegistry for ParentOnly {
+/// 	fn is_registered(location: &MultiLocation) -> bool {
+/// 		location == &MultiLocation::parent()
+/// 	}
+/// }
+///
+/// # fn main() {
+/// assert_eq!(<IsRegistered<ParentOnly> as MatchesFungible<u128>>::matches_fungible(&(Parent, 999).into()), Some(999));
+/// assert_eq!(<IsRegistered<ParentOnly> as MatchesFungible<u128>>::matches_fungible(&(Here, 999).into()), None);
+/// # }
+/// ```
+pub struct IsRegistered<R>(PhantomData<R>);
+impl<R: AssetRegistry, B: TryFrom<u128>> MatchesFungible<B> for IsRegistered<R> {
+	fn matches_fungible(a: &MultiAsset) -> Option<B> {
+		match (&a.id, &a.fun) {
+			(Concrete(ref id), Fungible(ref amount)) if R::is_registered(id) =>
+				CheckedConversion::checked_from(*amount),
+			_ => None,
+		}
+	}
+}
@@ -142,6 +142,14 @@ impl TransactAsset for TestAssetTransactor {
 				.map_err(|_| XcmError::NotWithdrawable)
 		})
 	}
+
+	fn can_check_out(_dest: &MultiLocation, _what: &MultiAsset) -> Result<(), XcmError> {
+		if CheckOutFails::get() {
+			Err(XcmError::NotWithdrawable)
+		} else {
+			Ok(())
+		}
+	}
 }
 
 pub fn to_account(l: MultiLocation) -> Result<u64, MultiLocation> {
@@ -262,6 +270,7 @@ parameter_types! {
 	// 1_000_000_000_000 => 1 unit of asset for 1 unit of Weight.
 	pub static WeightPrice: (AssetId, u128) = (From::from(Here), 1_000_000_000_000);
 	pub static MaxInstructions: u32 = 100;
+	pub static CheckOutFails: bool = false;
 }
 
 pub type TestBarrier = (
@@ -0,0 +1,132 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Adapters to work with `frame_support::traits::tokens::nonfungible` through XCM.
+
+use frame_support::traits::tokens::nonfungible;
+use sp_std::{marker::PhantomData, result};
+use xcm::latest::{Error as XcmError, MultiAsset, MultiLocation, Result};
+use xcm_executor::traits::{Convert, MatchesNonFungible, TransactAsset};
+
+/// Asset transaction errors.
+enum Error {
+	/// Asset not found.
+	AssetNotFound,
+	/// `MultiLocation` to `AccountId` conversion failed.
+	AccountIdConversionFailed,
+	/// The instance is not owned by the account it is withdrawn or transferred from.
+	NotOwner,
+	/// The instance to be teleported in already exists locally.
+	AlreadyExists,
+}
+
+impl From<Error> for XcmError {
+	fn from(e: Error) -> Self {
+		use XcmError::FailedToTransactAsset;
+		match e {
+			Error::AssetNotFound => XcmError::AssetNotFound,
+			Error::AccountIdConversionFailed => FailedToTransactAsset("AccountIdConversionFailed"),
+			Error::NotOwner => FailedToTransactAsset("NotOwner"),
+			Error::AlreadyExists => FailedToTransactAsset("AlreadyExists"),
+		}
+	}
+}
+
+/// Adapter to use a single collection of non-fungible instances as asset transactor, the
+/// counterpart of [`crate::CurrencyAdapter`] for unique assets.
+///
+/// Deposits mint the instance into the beneficiary and withdrawals burn it, so an instance only
+/// ever exists on one side of a teleport and no checking account is needed.
+pub struct NonFungibleAdapter<Assets, Matcher, AccountIdConverter, AccountId>(
+	PhantomData<(Assets, Matcher, AccountIdConverter, AccountId)>,
+);
+
+impl<
+		Assets: nonfungible::Mutate<AccountId> + nonfungible::Transfer<AccountId>,
+		Matcher: MatchesNonFungible<Assets::InstanceId>,
+		AccountIdConverter: Convert<MultiLocation, AccountId>,
+		AccountId: Clone + Eq, // can't get away without it since Assets is generic over it.
+	> NonFungibleAdapter<Assets, Matcher, AccountIdConverter, AccountId>
+{
+	/// Match `what` and ensure that its instance is owned by the account of `who`.
+	fn owned_instance(
+		what: &MultiAsset,
+		who: &MultiLocation,
+	) -> result::Result<(Assets::InstanceId, AccountId), Error> {
+		let instance = Matcher::matches_nonfungible(what).ok_or(Error::AssetNotFound)?;
+		let who =
+			AccountIdConverter::convert_ref(who).map_err(|()| Error::AccountIdConversionFailed)?;
+		if Assets::owner(&instance).as_ref() != Some(&who) {
+			return Err(Error::NotOwner)
+		}
+		Ok((instance, who))
+	}
+}
+
+impl<
+		Assets: nonfungible::Mutate<AccountId> + nonfungible::Transfer<AccountId>,
+		Matcher: MatchesNonFungible<Assets::InstanceId>,
+		AccountIdConverter: Convert<MultiLocation, AccountId>,
+		AccountId: Clone + Eq, // can't get away without it since Assets is generic over it.
+	> TransactAsset for NonFungibleAdapter<Assets, Matcher, AccountIdConverter, AccountId>
+{
+	fn can_check_in(_origin: &MultiLocation, what: &MultiAsset) -> Result {
+		log::trace!(target: "xcm::nonfungible_adapter", "can_check_in origin: {:?}, what: {:?}", _origin, what);
+		// Check we handle this asset.
+		let instance = Matcher::matches_nonfungible(what).ok_or(Error::AssetNotFound)?;
+		if Assets::owner(&instance).is_some() {
+			return Err(Error::AlreadyExists.into())
+		}
+		Ok(())
+	}
+
+	fn deposit_asset(what: &MultiAsset, who: &MultiLocation) -> Result {
+		log::trace!(target: "xcm::nonfungible_adapter", "deposit_asset what: {:?}, who: {:?}", what, who);
+		// Check we handle this asset.
+		let instance = Matcher::matches_nonfungible(what).ok_or(Error::AssetNotFound)?;
+		let who =
+			AccountIdConverter::convert_ref(who).map_err(|()| Error::AccountIdConversionFailed)?;
+		Assets::mint_into(&instance, &who).map_err(|e| XcmError::FailedToTransactAsset(e.into()))
+	}
+
+	fn withdraw_asset(
+		what: &MultiAsset,
+		who: &MultiLocation,
+	) -> result::Result<xcm_executor::Assets, XcmError> {
+		log::trace!(target: "xcm::nonfungible_adapter", "withdraw_asset what: {:?}, who: {:?}", what, who);
+		let (instance, _) = Self::owned_instance(what, who)?;
+		Assets::burn_from(&instance).map_err(|e| XcmError::FailedToTransactAsset(e.into()))?;
+		Ok(what.clone().into())
+	}
+
+	fn transfer_asset(
+		what: &MultiAsset,
+		from: &MultiLocation,
+		to: &MultiLocation,
+	) -> result::Result<xcm_executor::Assets, XcmError> {
+		log::trace!(
+			target: "xcm::nonfungible_adapter",
+			"transfer_asset what: {:?}, from: {:?}, to: {:?}",
+			what, from, to
+		);
+		let (instance, _) = Self::owned_instance(what, from)?;
+		let dest =
+			AccountIdConverter::convert_ref(to).map_err(|()| Error::AccountIdConversionFailed)?;
+		Assets::transfer(&instance, &dest)
+			.map_err(|e| XcmError::FailedToTransactAsset(e.into()))?;
+		Ok(what.clone().into())
+	}
+}
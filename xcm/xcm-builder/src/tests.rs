@@ -461,6 +461,37 @@ fn reserve_transfer_should_work() {
 	);
 }
 
+#[test]
+fn teleport_which_cannot_be_checked_out_should_leave_holding_unchanged() {
+	AllowUnpaidFrom::set(vec![X1(Parachain(1)).into()]);
+	CheckOutFails::set(true);
+	// Child parachain #1 owns 1000 tokens held by us in reserve.
+	add_asset(1001, (Here, 1000));
+
+	// They try to teleport 100 of them to #2, which the asset transactor refuses. The error handler
+	// deposits whatever is still in holding back into their sovereign account.
+	let r = XcmExecutor::<TestConfig>::execute_xcm(
+		Parachain(1),
+		Xcm(vec![
+			SetErrorHandler(Xcm(vec![DepositAsset {
+				assets: All.into(),
+				max_assets: 1,
+				beneficiary: Parachain(1).into(),
+			}])),
+			WithdrawAsset((Here, 100).into()),
+			InitiateTeleport {
+				assets: All.into(),
+				dest: Parachain(2).into(),
+				xcm: Xcm::<()>(vec![]),
+			},
+		]),
+		50,
+	);
+	assert_eq!(r, Outcome::Incomplete(40, XcmError::NotWithdrawable));
+	assert_eq!(assets(1001), vec![(Here, 1000).into()]);
+	assert_eq!(sent_xcm(), vec![]);
+}
+
 #[test]
 fn simple_version_subscriptions_should_work() {
 	AllowSubsFrom::set(vec![Parent.into()]);
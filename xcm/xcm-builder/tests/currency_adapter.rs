@@ -0,0 +1,2162 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+#[allow(dead_code)]
+mod mock;
+
+use frame_support::traits::{
+	Contains, Currency, ExistenceRequirement, Get, Imbalance, LockIdentifier, LockableCurrency,
+	ReservableCurrency, SignedImbalance, WithdrawReasons,
+};
+use mock::{
+	kusama_like_with_balances, AccountId, Balance, Balances, CheckAccount, Event, KsmLocation,
+	NarrowBalances, SovereignAccountOf, System, CENTS,
+};
+use polkadot_parachain::primitives::Id as ParaId;
+use sp_runtime::{traits::AccountIdConversion, DispatchError, DispatchResult};
+use sp_std::{cell::RefCell, collections::btree_set::BTreeSet};
+use xcm::latest::prelude::*;
+use xcm_builder::{
+	decimals_rate, AllowedLocations, AmountConversion, AssetRegistry, BurnCurrencyAdapter,
+	CheckingAccountDelta, CurrencyAdapter, CurrencyAdapterConfig, DepositStrategy, DustHandling,
+	EitherMatcher, InspectLock, IsAbstract, IsConcrete, IsRegistered, LockingCurrencyAdapter,
+	Rounding, UnknownAssetPolicy,
+};
+use xcm_executor::{
+	traits::{Convert, MatchesFungible, TransactAsset},
+	Assets,
+};
+
+const PARA_ID: u32 = 2000;
+const INITIAL_BALANCE: u128 = 100_000_000_000;
+
+type Adapter<Config = ()> = CurrencyAdapter<
+	Balances,
+	IsConcrete<KsmLocation>,
+	SovereignAccountOf,
+	AccountId,
+	CheckAccount,
+	Config,
+>;
+
+type UncheckedAdapter<Config = ()> =
+	CurrencyAdapter<Balances, IsConcrete<KsmLocation>, SovereignAccountOf, AccountId, (), Config>;
+
+type BurnAdapter =
+	BurnCurrencyAdapter<Balances, IsConcrete<KsmLocation>, SovereignAccountOf, AccountId>;
+
+fn para_location(id: u32) -> MultiLocation {
+	Parachain(id).into()
+}
+
+fn para_account(id: u32) -> AccountId {
+	ParaId::from(id).into_account()
+}
+
+thread_local! {
+	static HOOK_CALLS: RefCell<Vec<(&'static str, AccountId, Balance)>> = RefCell::new(Vec::new());
+}
+
+fn hook_calls() -> Vec<(&'static str, AccountId, Balance)> {
+	HOOK_CALLS.with(|c| c.borrow().clone())
+}
+
+pub struct RecordingHooks;
+impl CurrencyAdapterConfig<AccountId, Balances> for RecordingHooks {
+	fn on_deposit(who: &AccountId, _what: &MultiAsset, amount: Balance) {
+		HOOK_CALLS.with(|c| c.borrow_mut().push(("deposit", who.clone(), amount)));
+	}
+
+	fn on_withdraw(who: &AccountId, _what: &MultiAsset, amount: Balance) {
+		HOOK_CALLS.with(|c| c.borrow_mut().push(("withdraw", who.clone(), amount)));
+	}
+}
+
+#[test]
+fn hooks_fire_once_on_success() {
+	kusama_like_with_balances(vec![(para_account(PARA_ID), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(Adapter::<RecordingHooks>::deposit_asset(&asset, &para_location(3000)), Ok(()));
+		assert!(Adapter::<RecordingHooks>::withdraw_asset(&asset, &para_location(PARA_ID)).is_ok());
+		assert_eq!(
+			hook_calls(),
+			vec![
+				("deposit", para_account(3000), 10 * CENTS),
+				("withdraw", para_account(PARA_ID), 10 * CENTS),
+			]
+		);
+	});
+}
+
+#[test]
+fn hooks_do_not_fire_on_failure() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		let unknown: MultiAsset = (Parent, 10 * CENTS).into();
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		// Unknown asset.
+		assert_eq!(
+			Adapter::<RecordingHooks>::deposit_asset(&unknown, &para_location(PARA_ID)),
+			Err(XcmError::AssetNotFound)
+		);
+		// Unconvertible location.
+		assert!(Adapter::<RecordingHooks>::deposit_asset(&asset, &Parent.into()).is_err());
+		// Insufficient balance.
+		assert!(Adapter::<RecordingHooks>::withdraw_asset(&asset, &para_location(PARA_ID)).is_err());
+		assert!(hook_calls().is_empty());
+	});
+}
+
+pub struct IncludeReserved;
+impl CurrencyAdapterConfig<AccountId, Balances> for IncludeReserved {
+	const INCLUDE_RESERVED: bool = true;
+
+	fn unreserve(who: &AccountId, amount: Balance) -> Balance {
+		<Balances as ReservableCurrency<AccountId>>::unreserve(who, amount)
+	}
+}
+
+#[test]
+fn withdraw_uses_reserved_balance_only_when_enabled() {
+	let who = para_account(PARA_ID);
+	let asset: MultiAsset = (Here, 10 * CENTS).into();
+	kusama_like_with_balances(vec![(who.clone(), 15 * CENTS)]).execute_with(|| {
+		assert!(Balances::reserve(&who, 10 * CENTS).is_ok());
+		assert!(
+			<Adapter as TransactAsset>::withdraw_asset(&asset, &para_location(PARA_ID)).is_err()
+		);
+		assert_eq!(Balances::free_balance(&who), 5 * CENTS);
+		assert_eq!(Balances::reserved_balance(&who), 10 * CENTS);
+
+		assert!(Adapter::<IncludeReserved>::withdraw_asset(&asset, &para_location(PARA_ID)).is_ok());
+		assert_eq!(Balances::free_balance(&who), 0);
+		assert_eq!(Balances::reserved_balance(&who), 5 * CENTS);
+	});
+}
+
+#[test]
+fn failed_withdrawal_leaves_reserved_balance_reserved() {
+	let who = para_account(PARA_ID);
+	let asset: MultiAsset = (Here, 10 * CENTS).into();
+	kusama_like_with_balances(vec![(who.clone(), 15 * CENTS)]).execute_with(|| {
+		assert!(Balances::reserve(&who, 10 * CENTS).is_ok());
+		Balances::set_lock(*b"testlock", &who, 5 * CENTS, WithdrawReasons::all());
+		assert_eq!(
+			Adapter::<IncludeReserved>::withdraw_asset(&asset, &para_location(PARA_ID)).map(|_| ()),
+			Err(XcmError::FailedToTransactAsset("BalanceLocked"))
+		);
+		assert_eq!(Balances::free_balance(&who), 5 * CENTS);
+		assert_eq!(Balances::reserved_balance(&who), 10 * CENTS);
+	});
+}
+
+/// Includes reserved funds but keeps the default `unreserve`, which moves nothing.
+pub struct IncludeReservedWithoutUnreserve;
+impl CurrencyAdapterConfig<AccountId, Balances> for IncludeReservedWithoutUnreserve {
+	const INCLUDE_RESERVED: bool = true;
+}
+
+#[test]
+fn can_withdraw_asset_counts_only_what_unreserve_moves() {
+	let who = para_account(PARA_ID);
+	let asset: MultiAsset = (Here, 10 * CENTS).into();
+	kusama_like_with_balances(vec![(who.clone(), 15 * CENTS)]).execute_with(|| {
+		assert!(Balances::reserve(&who, 10 * CENTS).is_ok());
+		let insufficient = Err(XcmError::FailedToTransactAsset("InsufficientBalance"));
+		assert_eq!(
+			Adapter::<IncludeReservedWithoutUnreserve>::can_withdraw_asset(
+				&asset,
+				&para_location(PARA_ID)
+			),
+			insufficient
+		);
+		assert_eq!(
+			Adapter::<IncludeReservedWithoutUnreserve>::withdraw_asset(
+				&asset,
+				&para_location(PARA_ID)
+			)
+			.map(|_| ()),
+			insufficient
+		);
+
+		// With an `unreserve` which moves the shortfall both succeed, and the check moves nothing.
+		assert_eq!(
+			Adapter::<IncludeReserved>::can_withdraw_asset(&asset, &para_location(PARA_ID)),
+			Ok(())
+		);
+		assert_eq!(Balances::free_balance(&who), 5 * CENTS);
+		assert_eq!(Balances::reserved_balance(&who), 10 * CENTS);
+		assert!(Adapter::<IncludeReserved>::withdraw_asset(&asset, &para_location(PARA_ID)).is_ok());
+	});
+}
+
+#[test]
+fn can_withdraw_asset_does_not_count_reserved_funds_against_locks() {
+	let who = para_account(PARA_ID);
+	let asset: MultiAsset = (Here, 10 * CENTS).into();
+	kusama_like_with_balances(vec![(who.clone(), 30 * CENTS)]).execute_with(|| {
+		assert!(Balances::reserve(&who, 10 * CENTS).is_ok());
+		Balances::set_lock(*b"testlock", &who, 15 * CENTS, WithdrawReasons::all());
+		// The free balance covers the amount, so nothing is unreserved and only 10 cents would be
+		// left free, less than the lock.
+		let locked = Err(XcmError::FailedToTransactAsset("BalanceLocked"));
+		assert_eq!(
+			Adapter::<IncludeReserved>::can_withdraw_asset(&asset, &para_location(PARA_ID)),
+			locked
+		);
+		assert_eq!(
+			Adapter::<IncludeReserved>::withdraw_asset(&asset, &para_location(PARA_ID)).map(|_| ()),
+			locked
+		);
+		assert_eq!(Balances::free_balance(&who), 20 * CENTS);
+		assert_eq!(Balances::reserved_balance(&who), 10 * CENTS);
+	});
+}
+
+#[test]
+fn zero_amounts_are_rejected() {
+	let check_account = CheckAccount::get();
+	let balances =
+		vec![(para_account(PARA_ID), INITIAL_BALANCE), (check_account.clone(), INITIAL_BALANCE)];
+	kusama_like_with_balances(balances).execute_with(|| {
+		// Built by hand, as converting a zero `u128` into `Fungibility` asserts in debug builds.
+		let zero = MultiAsset { id: Concrete(Here.into()), fun: Fungible(0) };
+		let zero_amount = Err(XcmError::FailedToTransactAsset("ZeroAmount"));
+		assert_eq!(
+			<Adapter as TransactAsset>::deposit_asset(&zero, &para_location(3000)),
+			zero_amount
+		);
+		assert_eq!(
+			<Adapter as TransactAsset>::withdraw_asset(&zero, &para_location(PARA_ID)).map(|_| ()),
+			zero_amount
+		);
+		assert_eq!(<Adapter as TransactAsset>::can_check_in(&Parent.into(), &zero), zero_amount);
+		<Adapter as TransactAsset>::check_in(&Parent.into(), &zero);
+		assert_eq!(Balances::free_balance(&check_account), INITIAL_BALANCE);
+		assert_eq!(<Adapter as TransactAsset>::can_check_out(&Parent.into(), &zero), zero_amount);
+		<Adapter as TransactAsset>::check_out(&Parent.into(), &zero);
+		assert_eq!(Balances::free_balance(&check_account), INITIAL_BALANCE);
+		assert_eq!(
+			<BurnAdapter as TransactAsset>::can_check_in(&Parent.into(), &zero),
+			zero_amount
+		);
+		assert_eq!(Balances::free_balance(para_account(PARA_ID)), INITIAL_BALANCE);
+	});
+}
+
+#[test]
+fn deposit_below_minimum_balance_into_new_account_fails() {
+	kusama_like_with_balances(vec![(para_account(PARA_ID), INITIAL_BALANCE)]).execute_with(|| {
+		let dust: MultiAsset = (Here, CENTS / 2).into();
+		assert_eq!(
+			<Adapter as TransactAsset>::deposit_asset(&dust, &para_location(3000)),
+			Err(XcmError::FailedToTransactAsset("BelowMinimumBalance"))
+		);
+		assert_eq!(Balances::free_balance(para_account(3000)), 0);
+
+		assert_eq!(
+			<Adapter as TransactAsset>::deposit_asset(&dust, &para_location(PARA_ID)),
+			Ok(())
+		);
+		assert_eq!(Balances::free_balance(para_account(PARA_ID)), INITIAL_BALANCE + CENTS / 2);
+	});
+}
+
+pub struct RequireCheckingAccount;
+impl CurrencyAdapterConfig<AccountId, Balances> for RequireCheckingAccount {
+	const REQUIRE_CHECKING_ACCOUNT: bool = true;
+}
+
+#[test]
+fn can_check_in_without_checking_account_fails_in_strict_mode() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(
+			<UncheckedAdapter as TransactAsset>::can_check_in(&Parent.into(), &asset),
+			Ok(())
+		);
+		assert_eq!(
+			UncheckedAdapter::<RequireCheckingAccount>::can_check_in(&Parent.into(), &asset),
+			Err(XcmError::FailedToTransactAsset("NoCheckingAccount"))
+		);
+	});
+}
+
+#[test]
+fn withdraw_errors_distinguish_the_cause() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), 10 * CENTS)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 20 * CENTS).into();
+		assert_eq!(
+			<Adapter as TransactAsset>::withdraw_asset(&asset, &para_location(PARA_ID)).map(|_| ()),
+			Err(XcmError::FailedToTransactAsset("InsufficientBalance"))
+		);
+
+		Balances::set_lock(*b"testlock", &who, 5 * CENTS, WithdrawReasons::all());
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(
+			<Adapter as TransactAsset>::withdraw_asset(&asset, &para_location(PARA_ID)).map(|_| ()),
+			Err(XcmError::FailedToTransactAsset("BalanceLocked"))
+		);
+	});
+}
+
+#[test]
+fn burn_adapter_teleports_change_total_issuance() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		let dest: MultiLocation = Parachain(1000).into();
+
+		// Teleport out.
+		assert!(BurnAdapter::withdraw_asset(&asset, &para_location(PARA_ID)).is_ok());
+		BurnAdapter::check_out(&dest, &asset);
+		assert_eq!(Balances::total_issuance(), INITIAL_BALANCE - 10 * CENTS);
+
+		// Teleport in.
+		assert_eq!(BurnAdapter::can_check_in(&dest, &asset), Ok(()));
+		BurnAdapter::check_in(&dest, &asset);
+		assert_eq!(BurnAdapter::deposit_asset(&asset, &para_location(PARA_ID)), Ok(()));
+		assert_eq!(Balances::total_issuance(), INITIAL_BALANCE);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE);
+		assert_eq!(Balances::free_balance(CheckAccount::get()), 0);
+	});
+}
+
+/// A currency which only credits half of every deposit, to exercise the adapter's consistency
+/// checks.
+pub struct LossyCurrency;
+impl Currency<AccountId> for LossyCurrency {
+	type Balance = Balance;
+	type PositiveImbalance = <Balances as Currency<AccountId>>::PositiveImbalance;
+	type NegativeImbalance = <Balances as Currency<AccountId>>::NegativeImbalance;
+
+	fn total_balance(who: &AccountId) -> Balance {
+		<Balances as Currency<AccountId>>::total_balance(who)
+	}
+	fn can_slash(who: &AccountId, value: Balance) -> bool {
+		<Balances as Currency<AccountId>>::can_slash(who, value)
+	}
+	fn total_issuance() -> Balance {
+		<Balances as Currency<AccountId>>::total_issuance()
+	}
+	fn minimum_balance() -> Balance {
+		<Balances as Currency<AccountId>>::minimum_balance()
+	}
+	fn burn(amount: Balance) -> Self::PositiveImbalance {
+		<Balances as Currency<AccountId>>::burn(amount)
+	}
+	fn issue(amount: Balance) -> Self::NegativeImbalance {
+		<Balances as Currency<AccountId>>::issue(amount)
+	}
+	fn free_balance(who: &AccountId) -> Balance {
+		<Balances as Currency<AccountId>>::free_balance(who)
+	}
+	fn ensure_can_withdraw(
+		who: &AccountId,
+		amount: Balance,
+		reasons: WithdrawReasons,
+		new_balance: Balance,
+	) -> DispatchResult {
+		<Balances as Currency<AccountId>>::ensure_can_withdraw(who, amount, reasons, new_balance)
+	}
+	fn transfer(
+		source: &AccountId,
+		dest: &AccountId,
+		value: Balance,
+		existence_requirement: ExistenceRequirement,
+	) -> DispatchResult {
+		<Balances as Currency<AccountId>>::transfer(source, dest, value, existence_requirement)
+	}
+	fn slash(who: &AccountId, value: Balance) -> (Self::NegativeImbalance, Balance) {
+		<Balances as Currency<AccountId>>::slash(who, value)
+	}
+	fn deposit_into_existing(
+		who: &AccountId,
+		value: Balance,
+	) -> Result<Self::PositiveImbalance, DispatchError> {
+		<Balances as Currency<AccountId>>::deposit_into_existing(who, value / 2)
+	}
+	fn deposit_creating(who: &AccountId, value: Balance) -> Self::PositiveImbalance {
+		<Balances as Currency<AccountId>>::deposit_creating(who, value / 2)
+	}
+	fn withdraw(
+		who: &AccountId,
+		value: Balance,
+		reasons: WithdrawReasons,
+		liveness: ExistenceRequirement,
+	) -> Result<Self::NegativeImbalance, DispatchError> {
+		<Balances as Currency<AccountId>>::withdraw(who, value, reasons, liveness)
+	}
+	fn make_free_balance_be(
+		who: &AccountId,
+		balance: Balance,
+	) -> SignedImbalance<Balance, Self::PositiveImbalance> {
+		<Balances as Currency<AccountId>>::make_free_balance_be(who, balance)
+	}
+}
+
+type LossyAdapter =
+	CurrencyAdapter<LossyCurrency, IsConcrete<KsmLocation>, SovereignAccountOf, AccountId, ()>;
+
+#[test]
+fn consistent_operations_pass_debug_checks() {
+	kusama_like_with_balances(vec![(para_account(PARA_ID), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(<Adapter as TransactAsset>::deposit_asset(&asset, &para_location(3000)), Ok(()));
+		assert!(<Adapter as TransactAsset>::withdraw_asset(&asset, &para_location(3000)).is_ok());
+	});
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "deposit must increase the total issuance")]
+fn lossy_currency_trips_debug_checks() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		let _ = LossyAdapter::deposit_asset(&asset, &para_location(3000));
+	});
+}
+
+thread_local! {
+	static UNBALANCED: RefCell<Vec<Balance>> = RefCell::new(Vec::new());
+}
+
+/// Records the imbalance of every withdrawal.
+pub struct ObserveWithdrawals;
+impl CurrencyAdapterConfig<AccountId, Balances> for ObserveWithdrawals {
+	fn on_unbalanced(amount: &<Balances as Currency<AccountId>>::NegativeImbalance) {
+		UNBALANCED.with(|u| u.borrow_mut().push(amount.peek()));
+	}
+}
+
+#[test]
+fn withdrawn_imbalance_is_observed_and_burnt() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert!(
+			Adapter::<ObserveWithdrawals>::withdraw_asset(&asset, &para_location(PARA_ID)).is_ok()
+		);
+		assert_eq!(UNBALANCED.with(|u| u.borrow().clone()), vec![10 * CENTS]);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE - 10 * CENTS);
+		assert_eq!(Balances::total_issuance(), INITIAL_BALANCE - 10 * CENTS);
+
+		// Depositing the withdrawn assets again restores the total issuance, creating no value.
+		assert_eq!(
+			Adapter::<ObserveWithdrawals>::deposit_asset(&asset, &para_location(3000)),
+			Ok(())
+		);
+		assert_eq!(Balances::total_issuance(), INITIAL_BALANCE);
+	});
+}
+
+thread_local! {
+	static LOGS: RefCell<Vec<String>> = RefCell::new(Vec::new());
+}
+
+/// Logger which captures the `xcm::currency_adapter` records of the current thread.
+struct CapturingLogger;
+impl log::Log for CapturingLogger {
+	fn enabled(&self, _: &log::Metadata) -> bool {
+		true
+	}
+
+	fn log(&self, record: &log::Record) {
+		if record.target() == "xcm::currency_adapter" {
+			LOGS.with(|l| l.borrow_mut().push(format!("{}", record.args())));
+		}
+	}
+
+	fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger;
+
+fn capture_logs() {
+	static INIT: std::sync::Once = std::sync::Once::new();
+	INIT.call_once(|| {
+		log::set_logger(&LOGGER).expect("no other logger is set in this test binary; qed");
+		log::set_max_level(log::LevelFilter::Trace);
+	});
+	LOGS.with(|l| l.borrow_mut().clear());
+}
+
+fn logged(needle: &str) -> bool {
+	times_logged(needle) > 0
+}
+
+fn times_logged(needle: &str) -> usize {
+	LOGS.with(|l| l.borrow().iter().filter(|line| line.contains(needle)).count())
+}
+
+#[test]
+fn conversion_failures_map_to_xcm_errors_and_log_context() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		capture_logs();
+		let unknown: MultiAsset = (Parent, 10 * CENTS).into();
+		assert_eq!(
+			<Adapter as TransactAsset>::deposit_asset(&unknown, &para_location(PARA_ID)),
+			Err(XcmError::AssetNotFound)
+		);
+		assert!(logged("AssetNotFound(MultiAsset { id: Concrete(MultiLocation { parents: 1"));
+
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		let location: MultiLocation = GeneralIndex(7).into();
+		assert_eq!(
+			<Adapter as TransactAsset>::deposit_asset(&asset, &location),
+			Err(XcmError::FailedToTransactAsset("AccountIdConversionFailed"))
+		);
+		assert!(logged("AccountIdConversionFailed(MultiLocation { parents: 0, interior: X1(GeneralIndex(7)) })"));
+		// Each failure is logged once.
+		assert_eq!(times_logged("AccountIdConversionFailed"), 1);
+	});
+}
+
+/// Matches the relay chain token, rounding amounts down to whole cents.
+pub struct RoundingMatcher;
+impl MatchesFungible<Balance> for RoundingMatcher {
+	fn matches_fungible(a: &MultiAsset) -> Option<Balance> {
+		<IsConcrete<KsmLocation> as MatchesFungible<Balance>>::matches_fungible(a)
+			.map(|amount| amount / CENTS * CENTS)
+	}
+}
+
+type RoundingAdapter =
+	CurrencyAdapter<Balances, RoundingMatcher, SovereignAccountOf, AccountId, CheckAccount>;
+
+#[test]
+fn withdraw_returns_the_amount_actually_withdrawn() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS + 1).into();
+		let withdrawn = RoundingAdapter::withdraw_asset(&asset, &para_location(PARA_ID)).unwrap();
+		assert_eq!(withdrawn, Assets::from(MultiAsset::from((Here, 10 * CENTS))));
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE - 10 * CENTS);
+	});
+}
+
+#[test]
+fn can_withdraw_asset_does_not_change_state() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), 10 * CENTS)]).execute_with(|| {
+		let affordable: MultiAsset = (Here, 5 * CENTS).into();
+		let too_much: MultiAsset = (Here, 20 * CENTS).into();
+		assert_eq!(Adapter::<()>::can_withdraw_asset(&affordable, &para_location(PARA_ID)), Ok(()));
+		assert_eq!(
+			Adapter::<()>::can_withdraw_asset(&too_much, &para_location(PARA_ID)),
+			Err(XcmError::FailedToTransactAsset("InsufficientBalance"))
+		);
+		assert_eq!(Balances::free_balance(&who), 10 * CENTS);
+		assert_eq!(Balances::total_issuance(), 10 * CENTS);
+	});
+}
+
+#[test]
+fn non_fungible_assets_are_reported_clearly() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		let ours: MultiAsset = (Here, Index(1)).into();
+		assert_eq!(
+			<Adapter as TransactAsset>::deposit_asset(&ours, &para_location(PARA_ID)),
+			Err(XcmError::FailedToTransactAsset("ExpectedFungible"))
+		);
+		assert_eq!(
+			<Adapter as TransactAsset>::can_check_in(&Parent.into(), &ours),
+			Err(XcmError::FailedToTransactAsset("ExpectedFungible"))
+		);
+		assert_eq!(
+			<Adapter as TransactAsset>::can_check_out(&Parent.into(), &ours),
+			Err(XcmError::FailedToTransactAsset("ExpectedFungible"))
+		);
+
+		// Non-fungibles of other classes are left to other transactors.
+		let theirs: MultiAsset = (Parent, Index(1)).into();
+		assert_eq!(
+			<Adapter as TransactAsset>::deposit_asset(&theirs, &para_location(PARA_ID)),
+			Err(XcmError::AssetNotFound)
+		);
+	});
+}
+
+pub struct FeeReasons;
+impl CurrencyAdapterConfig<AccountId, Balances> for FeeReasons {
+	const WITHDRAW_REASONS: WithdrawReasons =
+		WithdrawReasons::TRANSFER.union(WithdrawReasons::TRANSACTION_PAYMENT);
+}
+
+#[test]
+fn configured_withdraw_reasons_are_used() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), INITIAL_BALANCE)]).execute_with(|| {
+		// Lock everything, but only against transaction payment.
+		Balances::set_lock(
+			*b"feelock_",
+			&who,
+			INITIAL_BALANCE,
+			WithdrawReasons::TRANSACTION_PAYMENT,
+		);
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(
+			Adapter::<FeeReasons>::can_withdraw_asset(&asset, &para_location(PARA_ID)),
+			Err(XcmError::FailedToTransactAsset("BalanceLocked"))
+		);
+		assert_eq!(
+			Adapter::<FeeReasons>::withdraw_asset(&asset, &para_location(PARA_ID)).map(|_| ()),
+			Err(XcmError::FailedToTransactAsset("BalanceLocked"))
+		);
+		assert!(<Adapter as TransactAsset>::withdraw_asset(&asset, &para_location(PARA_ID)).is_ok());
+	});
+}
+
+thread_local! {
+	static COUNTERS: RefCell<(u32, u32, u32)> = RefCell::new((0, 0, 0));
+}
+
+/// Counts deposits, withdrawals and failures.
+pub struct Metrics;
+impl CurrencyAdapterConfig<AccountId, Balances> for Metrics {
+	fn on_deposit(_: &AccountId, _: &MultiAsset, _: Balance) {
+		COUNTERS.with(|c| c.borrow_mut().0 += 1);
+	}
+
+	fn on_withdraw(_: &AccountId, _: &MultiAsset, _: Balance) {
+		COUNTERS.with(|c| c.borrow_mut().1 += 1);
+	}
+
+	fn on_failure(_: &MultiAsset, _: &MultiLocation, _: &XcmError) {
+		COUNTERS.with(|c| c.borrow_mut().2 += 1);
+	}
+}
+
+#[test]
+fn metrics_count_successes_and_failures() {
+	kusama_like_with_balances(vec![(para_account(PARA_ID), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		let dust: MultiAsset = (Here, CENTS / 2).into();
+		let unknown: MultiAsset = (Parent, 10 * CENTS).into();
+
+		assert!(Adapter::<Metrics>::deposit_asset(&asset, &para_location(3000)).is_ok());
+		assert!(Adapter::<Metrics>::deposit_asset(&dust, &para_location(4000)).is_err());
+		assert!(Adapter::<Metrics>::withdraw_asset(&asset, &para_location(PARA_ID)).is_ok());
+		assert!(Adapter::<Metrics>::withdraw_asset(&asset, &para_location(4000)).is_err());
+		// Assets of other transactors are not failures.
+		assert!(Adapter::<Metrics>::withdraw_asset(&unknown, &para_location(PARA_ID)).is_err());
+
+		assert_eq!(COUNTERS.with(|c| *c.borrow()), (1, 1, 2));
+	});
+}
+
+#[test]
+fn failed_transfer_leaves_the_source_untouched() {
+	kusama_like_with_balances(vec![(para_account(PARA_ID), INITIAL_BALANCE)]).execute_with(|| {
+		let dust: MultiAsset = (Here, CENTS / 2).into();
+		assert_eq!(
+			<Adapter as TransactAsset>::transfer_asset(
+				&dust,
+				&para_location(PARA_ID),
+				&para_location(3000)
+			),
+			Err(XcmError::FailedToTransactAsset("BelowMinimumBalance"))
+		);
+		assert_eq!(Balances::free_balance(para_account(PARA_ID)), INITIAL_BALANCE);
+		assert_eq!(Balances::free_balance(para_account(3000)), 0);
+
+		// The same holds for the adapter without a checking account.
+		assert_eq!(
+			<BurnAdapter as TransactAsset>::transfer_asset(
+				&dust,
+				&para_location(PARA_ID),
+				&para_location(3000)
+			),
+			Err(XcmError::FailedToTransactAsset("BelowMinimumBalance"))
+		);
+		assert_eq!(Balances::free_balance(para_account(PARA_ID)), INITIAL_BALANCE);
+
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(
+			<Adapter as TransactAsset>::transfer_asset(
+				&asset,
+				&para_location(PARA_ID),
+				&para_location(3000)
+			),
+			Ok(asset.into())
+		);
+		assert_eq!(Balances::free_balance(para_account(PARA_ID)), INITIAL_BALANCE - 10 * CENTS);
+		assert_eq!(Balances::free_balance(para_account(3000)), 10 * CENTS);
+	});
+}
+
+#[test]
+fn transfer_uses_currency_transfer_unless_configured_otherwise() {
+	let transferred = |events: Vec<_>| {
+		events.into_iter().any(|r: frame_system::EventRecord<Event, _>| {
+			matches!(r.event, Event::Balances(pallet_balances::Event::Transfer { .. }))
+		})
+	};
+	let asset: MultiAsset = (Here, 10 * CENTS).into();
+	let direct = kusama_like_with_balances(vec![(para_account(PARA_ID), INITIAL_BALANCE)])
+		.execute_with(|| {
+			let result = <Adapter as TransactAsset>::transfer_asset(
+				&asset,
+				&para_location(PARA_ID),
+				&para_location(3000),
+			);
+			assert!(transferred(System::events()));
+			(result, Balances::free_balance(para_account(3000)), Balances::total_issuance())
+		});
+	// Counting reserved funds needs the withdraw and deposit path.
+	let indirect = kusama_like_with_balances(vec![(para_account(PARA_ID), INITIAL_BALANCE)])
+		.execute_with(|| {
+			let result = Adapter::<IncludeReserved>::transfer_asset(
+				&asset,
+				&para_location(PARA_ID),
+				&para_location(3000),
+			);
+			assert!(!transferred(System::events()));
+			(result, Balances::free_balance(para_account(3000)), Balances::total_issuance())
+		});
+	assert_eq!(direct.0, Ok(asset.clone().into()));
+	assert_eq!(direct, indirect);
+}
+
+/// The number of `Transfer` events of the balances pallet.
+fn balance_transfers() -> usize {
+	System::events()
+		.into_iter()
+		.filter(|r| matches!(r.event, Event::Balances(pallet_balances::Event::Transfer { .. })))
+		.count()
+}
+
+/// Deposits from the checking account, and withdrawals which may use the reserved balance.
+pub struct ReservedAndReserveDeposits;
+impl CurrencyAdapterConfig<AccountId, Balances> for ReservedAndReserveDeposits {
+	const INCLUDE_RESERVED: bool = true;
+	const DEPOSIT_STRATEGY: DepositStrategy = DepositStrategy::TransferFromCheckingAccount;
+
+	fn unreserve(who: &AccountId, amount: Balance) -> Balance {
+		<Balances as ReservableCurrency<AccountId>>::unreserve(who, amount)
+	}
+}
+
+#[test]
+fn transfer_moves_funds_once_whatever_the_deposit_strategy() {
+	let balances =
+		vec![(para_account(PARA_ID), INITIAL_BALANCE), (CheckAccount::get(), INITIAL_BALANCE)];
+	kusama_like_with_balances(balances).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(
+			Adapter::<Metrics>::transfer_asset(
+				&asset,
+				&para_location(PARA_ID),
+				&para_location(3000)
+			),
+			Ok(asset.clone().into())
+		);
+		assert_eq!(balance_transfers(), 1);
+		assert_eq!(COUNTERS.with(|c| *c.borrow()), (1, 1, 0));
+
+		// The funds come from the sender, not the checking account.
+		assert_eq!(
+			Adapter::<ReserveDeposits>::transfer_asset(
+				&asset,
+				&para_location(PARA_ID),
+				&para_location(3000)
+			),
+			Ok(asset.clone().into())
+		);
+		assert_eq!(balance_transfers(), 2);
+		assert_eq!(Balances::free_balance(CheckAccount::get()), INITIAL_BALANCE);
+		assert_eq!(Balances::free_balance(para_account(PARA_ID)), INITIAL_BALANCE - 20 * CENTS);
+		assert_eq!(Balances::free_balance(para_account(3000)), 20 * CENTS);
+
+		// Also if the funds have to be withdrawn and deposited rather than transferred.
+		let issuance = Balances::total_issuance();
+		assert_eq!(
+			Adapter::<ReservedAndReserveDeposits>::transfer_asset(
+				&asset,
+				&para_location(PARA_ID),
+				&para_location(3000)
+			),
+			Ok(asset.clone().into())
+		);
+		assert_eq!(Balances::free_balance(CheckAccount::get()), INITIAL_BALANCE);
+		assert_eq!(Balances::free_balance(para_account(PARA_ID)), INITIAL_BALANCE - 30 * CENTS);
+		assert_eq!(Balances::free_balance(para_account(3000)), 30 * CENTS);
+		assert_eq!(Balances::total_issuance(), issuance);
+
+		// Forbidden dust is still refused.
+		let leaving_dust: MultiAsset = (Here, INITIAL_BALANCE - 30 * CENTS - CENTS / 2).into();
+		assert_eq!(
+			Adapter::<ForbidDust>::transfer_asset(
+				&leaving_dust,
+				&para_location(PARA_ID),
+				&para_location(3000)
+			),
+			Err(XcmError::FailedToTransactAsset("BelowMinimumBalance"))
+		);
+		assert_eq!(balance_transfers(), 2);
+	});
+}
+
+thread_local! {
+	static CONVERSIONS: RefCell<u32> = RefCell::new(0);
+}
+
+/// `SovereignAccountOf`, counting its conversions.
+pub struct CountingConverter;
+impl Convert<MultiLocation, AccountId> for CountingConverter {
+	fn convert(value: MultiLocation) -> Result<AccountId, MultiLocation> {
+		CONVERSIONS.with(|c| *c.borrow_mut() += 1);
+		SovereignAccountOf::convert(value)
+	}
+
+	fn reverse(value: AccountId) -> Result<MultiLocation, AccountId> {
+		SovereignAccountOf::reverse(value)
+	}
+}
+
+type CountingAdapter<Config = ()> = CurrencyAdapter<
+	Balances,
+	IsConcrete<KsmLocation>,
+	CountingConverter,
+	AccountId,
+	CheckAccount,
+	Config,
+>;
+
+#[test]
+fn transfer_converts_each_location_once() {
+	kusama_like_with_balances(vec![(para_account(PARA_ID), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert!(<CountingAdapter as TransactAsset>::transfer_asset(
+			&asset,
+			&para_location(PARA_ID),
+			&para_location(3000)
+		)
+		.is_ok());
+		assert_eq!(CONVERSIONS.with(|c| c.replace(0)), 2);
+
+		assert!(CountingAdapter::<IncludeReserved>::transfer_asset(
+			&asset,
+			&para_location(PARA_ID),
+			&para_location(3000)
+		)
+		.is_ok());
+		assert_eq!(CONVERSIONS.with(|c| c.replace(0)), 2);
+
+		assert_eq!(
+			<CountingAdapter>::resolve_account(&para_location(3000)),
+			Ok(para_account(3000))
+		);
+		assert_eq!(
+			<CountingAdapter>::resolve_account(&Parent.into()),
+			Err(XcmError::FailedToTransactAsset("AccountIdConversionFailed"))
+		);
+	});
+}
+
+#[test]
+fn checked_out_assets_can_be_checked_back_in() {
+	// The checking account is kept alive by its minimum balance.
+	kusama_like_with_balances(vec![(CheckAccount::get(), CENTS)]).execute_with(|| {
+		let dest: MultiLocation = Parachain(1000).into();
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(<Adapter as TransactAsset>::can_check_out(&dest, &asset), Ok(()));
+		<Adapter as TransactAsset>::check_out(&dest, &asset);
+		assert_eq!(Balances::free_balance(CheckAccount::get()), 11 * CENTS);
+
+		assert_eq!(<Adapter as TransactAsset>::can_check_in(&dest, &asset), Ok(()));
+		<Adapter as TransactAsset>::check_in(&dest, &asset);
+		assert_eq!(Balances::free_balance(CheckAccount::get()), CENTS);
+
+		// Nothing more was checked out, so nothing more can be checked in.
+		assert!(<Adapter as TransactAsset>::can_check_in(&dest, &asset).is_err());
+	});
+}
+
+pub struct FundedCheckingAccount;
+impl CurrencyAdapterConfig<AccountId, Balances> for FundedCheckingAccount {
+	const FUND_CHECKING_ACCOUNT: bool = true;
+}
+
+#[test]
+fn funded_checking_account_does_not_leak_its_minimum_balance() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		let dest: MultiLocation = Parachain(1000).into();
+		let checking = CheckAccount::get();
+		// Without funding, a teleport into the empty account cannot all come back.
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		<Adapter as TransactAsset>::check_out(&dest, &asset);
+		assert!(<Adapter as TransactAsset>::can_check_in(&dest, &asset).is_err());
+		<Adapter as TransactAsset>::check_in(&dest, &(Here, 9 * CENTS).into());
+		assert_eq!(Balances::free_balance(&checking), CENTS);
+		let _ = Balances::slash(&checking, CENTS);
+		assert_eq!(Balances::total_balance(&checking), 0);
+
+		// With funding, the account is created with its minimum balance on top, which stays put
+		// while the liability drops to zero again and again. Teleports below the minimum balance
+		// can be checked out too.
+		let issuance = Balances::total_issuance();
+		for amount in [CENTS / 2, 10 * CENTS, CENTS, 3 * CENTS] {
+			let asset: MultiAsset = (Here, amount).into();
+			assert_eq!(Adapter::<FundedCheckingAccount>::can_check_out(&dest, &asset), Ok(()));
+			Adapter::<FundedCheckingAccount>::check_out(&dest, &asset);
+			assert_eq!(Balances::free_balance(&checking), CENTS + amount);
+			assert_eq!(Adapter::<FundedCheckingAccount>::can_check_in(&dest, &asset), Ok(()));
+			Adapter::<FundedCheckingAccount>::check_in(&dest, &asset);
+			assert_eq!(Balances::free_balance(&checking), CENTS);
+		}
+		assert_eq!(Balances::total_issuance(), issuance + CENTS);
+	});
+}
+
+#[test]
+fn check_out_which_cannot_be_recorded_is_rejected() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		let dest: MultiLocation = Parachain(1000).into();
+		let dust: MultiAsset = (Here, CENTS / 2).into();
+		assert_eq!(
+			<Adapter as TransactAsset>::can_check_out(&dest, &dust),
+			Err(XcmError::FailedToTransactAsset("BelowMinimumBalance"))
+		);
+		// Assets of other transactors need no bookkeeping.
+		let other: MultiAsset = (Parent, CENTS / 2).into();
+		assert_eq!(<Adapter as TransactAsset>::can_check_out(&dest, &other), Ok(()));
+	});
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "`can_check_out` must have returned `true` immediately prior")]
+fn unchecked_check_out_trips_debug_check() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		let dust: MultiAsset = (Here, CENTS / 2).into();
+		<Adapter as TransactAsset>::check_out(&Parachain(1000).into(), &dust);
+	});
+}
+
+pub struct ReserveDeposits;
+impl CurrencyAdapterConfig<AccountId, Balances> for ReserveDeposits {
+	const DEPOSIT_STRATEGY: DepositStrategy = DepositStrategy::TransferFromCheckingAccount;
+}
+
+#[test]
+fn reserve_deposits_do_not_change_total_issuance() {
+	kusama_like_with_balances(vec![(CheckAccount::get(), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		let issuance = Balances::total_issuance();
+		assert_eq!(Adapter::<ReserveDeposits>::deposit_asset(&asset, &para_location(3000)), Ok(()));
+		assert_eq!(Balances::total_issuance(), issuance);
+		assert_eq!(Balances::free_balance(para_account(3000)), 10 * CENTS);
+		assert_eq!(Balances::free_balance(CheckAccount::get()), INITIAL_BALANCE - 10 * CENTS);
+
+		// Deposits are limited by what the checking account holds.
+		let too_much: MultiAsset = (Here, INITIAL_BALANCE).into();
+		assert_eq!(
+			Adapter::<ReserveDeposits>::deposit_asset(&too_much, &para_location(3000)),
+			Err(XcmError::FailedToTransactAsset("InsufficientBalance"))
+		);
+		assert_eq!(Balances::total_issuance(), issuance);
+
+		// Without a checking account there is nothing to transfer from.
+		assert_eq!(
+			UncheckedAdapter::<ReserveDeposits>::deposit_asset(&asset, &para_location(3000)),
+			Err(XcmError::FailedToTransactAsset("NoCheckingAccount"))
+		);
+	});
+}
+
+#[test]
+fn reserve_withdrawals_return_funds_to_the_checking_account() {
+	let who = para_account(PARA_ID);
+	let balances = vec![(who.clone(), INITIAL_BALANCE), (CheckAccount::get(), INITIAL_BALANCE)];
+	kusama_like_with_balances(balances).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		let issuance = Balances::total_issuance();
+		assert!(Adapter::<ReserveDeposits>::withdraw_asset(&asset, &para_location(PARA_ID)).is_ok());
+		assert_eq!(Balances::free_balance(CheckAccount::get()), INITIAL_BALANCE + 10 * CENTS);
+		assert_eq!(Balances::total_issuance(), issuance);
+		assert_eq!(
+			Adapter::<ReserveDeposits>::deposit_asset(&asset, &para_location(PARA_ID)),
+			Ok(())
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE);
+		assert_eq!(Balances::free_balance(CheckAccount::get()), INITIAL_BALANCE);
+		assert_eq!(Balances::total_issuance(), issuance);
+
+		// Teleports are accounted for by the withdrawal and the deposit alone.
+		let dest: MultiLocation = Parachain(1000).into();
+		assert!(Adapter::<ReserveDeposits>::withdraw_asset(&asset, &para_location(PARA_ID)).is_ok());
+		assert_eq!(Adapter::<ReserveDeposits>::can_check_out(&dest, &asset), Ok(()));
+		Adapter::<ReserveDeposits>::check_out(&dest, &asset);
+		assert_eq!(Balances::free_balance(CheckAccount::get()), INITIAL_BALANCE + 10 * CENTS);
+		assert_eq!(Adapter::<ReserveDeposits>::can_check_in(&dest, &asset), Ok(()));
+		Adapter::<ReserveDeposits>::check_in(&dest, &asset);
+		assert_eq!(
+			Adapter::<ReserveDeposits>::deposit_asset(&asset, &para_location(PARA_ID)),
+			Ok(())
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE);
+		assert_eq!(Balances::free_balance(CheckAccount::get()), INITIAL_BALANCE);
+		assert_eq!(Balances::total_issuance(), issuance);
+
+		// Without a checking account there is nothing to return the funds to.
+		assert_eq!(
+			UncheckedAdapter::<ReserveDeposits>::withdraw_asset(&asset, &para_location(PARA_ID))
+				.map(|_| ()),
+			Err(XcmError::FailedToTransactAsset("NoCheckingAccount"))
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE);
+	});
+}
+
+pub struct ReserveBacked;
+impl CurrencyAdapterConfig<AccountId, Balances> for ReserveBacked {
+	const DEPOSIT_STRATEGY: DepositStrategy = DepositStrategy::TransferFromReserveAccount;
+	fn reserve_location(what: &MultiAsset) -> Option<MultiLocation> {
+		matches!(what.id, Concrete(MultiLocation { parents: 0, interior: Here }))
+			.then(|| para_location(PARA_ID))
+	}
+}
+
+#[test]
+fn reserve_backed_deposits_are_paid_by_the_reserve_account() {
+	kusama_like_with_balances(vec![(para_account(PARA_ID), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		let issuance = Balances::total_issuance();
+		assert_eq!(Adapter::<ReserveBacked>::deposit_asset(&asset, &para_location(3000)), Ok(()));
+		assert_eq!(Balances::total_issuance(), issuance);
+		assert_eq!(Balances::free_balance(para_account(3000)), 10 * CENTS);
+		assert_eq!(Balances::free_balance(para_account(PARA_ID)), INITIAL_BALANCE - 10 * CENTS);
+
+		// A deposit into the reserve location itself moves nothing.
+		assert_eq!(
+			Adapter::<ReserveBacked>::deposit_asset(&asset, &para_location(PARA_ID)),
+			Ok(())
+		);
+		assert_eq!(Balances::free_balance(para_account(PARA_ID)), INITIAL_BALANCE - 10 * CENTS);
+
+		// The reserve account is kept alive.
+		let everything: MultiAsset = (Here, INITIAL_BALANCE - 10 * CENTS).into();
+		assert!(Adapter::<ReserveBacked>::deposit_asset(&everything, &para_location(3000)).is_err());
+		assert_eq!(Balances::total_issuance(), issuance);
+
+		// Assets without a reserve location cannot be deposited.
+		type AbstractAdapter = CurrencyAdapter<
+			Balances,
+			EitherMatcher<IsConcrete<KsmLocation>, IsAbstract<KsmName>>,
+			SovereignAccountOf,
+			AccountId,
+			CheckAccount,
+			ReserveBacked,
+		>;
+		let abstract_asset: MultiAsset = (b"KSM".to_vec(), 10 * CENTS).into();
+		assert_eq!(
+			AbstractAdapter::deposit_asset(&abstract_asset, &para_location(3000)),
+			Err(XcmError::FailedToTransactAsset("NoReserveLocation"))
+		);
+	});
+}
+
+#[test]
+fn reserve_backed_withdrawals_return_funds_to_the_reserve_account() {
+	let reserve = para_account(PARA_ID);
+	let who = para_account(3000);
+	let balances = vec![(reserve.clone(), INITIAL_BALANCE), (who.clone(), INITIAL_BALANCE)];
+	kusama_like_with_balances(balances).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		let issuance = Balances::total_issuance();
+		assert!(Adapter::<ReserveBacked>::withdraw_asset(&asset, &para_location(3000)).is_ok());
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE - 10 * CENTS);
+		assert_eq!(Balances::free_balance(&reserve), INITIAL_BALANCE + 10 * CENTS);
+		assert_eq!(Balances::total_issuance(), issuance);
+		assert_eq!(Adapter::<ReserveBacked>::deposit_asset(&asset, &para_location(3000)), Ok(()));
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE);
+		assert_eq!(Balances::free_balance(&reserve), INITIAL_BALANCE);
+		assert_eq!(Balances::total_issuance(), issuance);
+
+		// Teleports are accounted for by the withdrawal and the deposit alone.
+		let dest: MultiLocation = Parachain(1000).into();
+		assert!(Adapter::<ReserveBacked>::withdraw_asset(&asset, &para_location(3000)).is_ok());
+		Adapter::<ReserveBacked>::check_out(&dest, &asset);
+		assert_eq!(Balances::free_balance(CheckAccount::get()), 0);
+		Adapter::<ReserveBacked>::check_in(&dest, &asset);
+		assert_eq!(Adapter::<ReserveBacked>::deposit_asset(&asset, &para_location(3000)), Ok(()));
+		assert_eq!(Balances::free_balance(&reserve), INITIAL_BALANCE);
+		assert_eq!(Balances::total_issuance(), issuance);
+
+		// Without a reserve location there is nothing to return the funds to.
+		type AbstractAdapter = CurrencyAdapter<
+			Balances,
+			EitherMatcher<IsConcrete<KsmLocation>, IsAbstract<KsmName>>,
+			SovereignAccountOf,
+			AccountId,
+			CheckAccount,
+			ReserveBacked,
+		>;
+		let abstract_asset: MultiAsset = (b"KSM".to_vec(), 10 * CENTS).into();
+		assert_eq!(
+			AbstractAdapter::withdraw_asset(&abstract_asset, &para_location(3000)).map(|_| ()),
+			Err(XcmError::FailedToTransactAsset("NoReserveLocation"))
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE);
+	});
+}
+
+#[test]
+fn outcomes_are_logged_with_account_and_amount() {
+	kusama_like_with_balances(vec![(para_account(PARA_ID), INITIAL_BALANCE)]).execute_with(|| {
+		capture_logs();
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(
+			<Adapter as TransactAsset>::deposit_asset(&asset, &para_location(PARA_ID)),
+			Ok(())
+		);
+		assert!(logged(&format!(
+			"deposit_asset succeeded: who: {:?}, amount: {:?}",
+			para_account(PARA_ID),
+			10 * CENTS
+		)));
+
+		assert!(<Adapter as TransactAsset>::withdraw_asset(&asset, &para_location(3000)).is_err());
+		assert!(logged("withdraw_asset failed: who: MultiLocation"));
+	});
+}
+
+pub struct ForbidDust;
+impl CurrencyAdapterConfig<AccountId, Balances> for ForbidDust {
+	const DUST_HANDLING: DustHandling = DustHandling::Forbid;
+}
+
+pub struct SweepDust;
+impl CurrencyAdapterConfig<AccountId, Balances> for SweepDust {
+	const DUST_HANDLING: DustHandling = DustHandling::Sweep;
+
+	fn on_dust(dust: <Balances as Currency<AccountId>>::NegativeImbalance) {
+		Balances::resolve_creating(&para_account(1), dust);
+	}
+}
+
+#[test]
+fn withdrawal_leaving_dust_follows_the_dust_handling() {
+	let who = para_account(PARA_ID);
+	let balances = vec![(who.clone(), 10 * CENTS + CENTS / 2), (para_account(1), INITIAL_BALANCE)];
+	let asset: MultiAsset = (Here, 10 * CENTS).into();
+
+	// By default the account is reaped and the dust lost.
+	kusama_like_with_balances(balances.clone()).execute_with(|| {
+		let issuance = Balances::total_issuance();
+		assert!(<Adapter as TransactAsset>::withdraw_asset(&asset, &para_location(PARA_ID)).is_ok());
+		assert_eq!(Balances::total_balance(&who), 0);
+		assert_eq!(Balances::total_issuance(), issuance - 10 * CENTS - CENTS / 2);
+	});
+
+	kusama_like_with_balances(balances.clone()).execute_with(|| {
+		assert_eq!(
+			Adapter::<ForbidDust>::can_withdraw_asset(&asset, &para_location(PARA_ID)),
+			Err(XcmError::FailedToTransactAsset("BelowMinimumBalance"))
+		);
+		assert_eq!(
+			Adapter::<ForbidDust>::withdraw_asset(&asset, &para_location(PARA_ID)).map(|_| ()),
+			Err(XcmError::FailedToTransactAsset("BelowMinimumBalance"))
+		);
+		assert_eq!(Balances::free_balance(&who), 10 * CENTS + CENTS / 2);
+
+		// Emptying the account leaves no dust.
+		let everything: MultiAsset = (Here, 10 * CENTS + CENTS / 2).into();
+		assert!(Adapter::<ForbidDust>::withdraw_asset(&everything, &para_location(PARA_ID)).is_ok());
+		assert_eq!(Balances::total_balance(&who), 0);
+	});
+
+	kusama_like_with_balances(balances.clone()).execute_with(|| {
+		let issuance = Balances::total_issuance();
+		assert_eq!(
+			Adapter::<SweepDust>::withdraw_asset(&asset, &para_location(PARA_ID)),
+			Ok(asset.clone().into())
+		);
+		assert_eq!(Balances::total_balance(&who), 0);
+		assert_eq!(Balances::free_balance(para_account(1)), INITIAL_BALANCE + CENTS / 2);
+		assert_eq!(Balances::total_issuance(), issuance - 10 * CENTS);
+	});
+
+	// The dust swept along with the amount is subject to locks as well.
+	kusama_like_with_balances(balances).execute_with(|| {
+		Balances::set_lock(*b"testlock", &who, CENTS / 2, WithdrawReasons::all());
+		assert_eq!(
+			Adapter::<SweepDust>::can_withdraw_asset(&asset, &para_location(PARA_ID)),
+			Err(XcmError::FailedToTransactAsset("BalanceLocked"))
+		);
+		assert_eq!(
+			Adapter::<SweepDust>::withdraw_asset(&asset, &para_location(PARA_ID)).map(|_| ()),
+			Err(XcmError::FailedToTransactAsset("BalanceLocked"))
+		);
+	});
+}
+
+frame_support::parameter_types! {
+	pub KsmName: &'static [u8] = b"KSM";
+}
+
+type MigratingAdapter = CurrencyAdapter<
+	Balances,
+	EitherMatcher<IsConcrete<KsmLocation>, IsAbstract<KsmName>>,
+	SovereignAccountOf,
+	AccountId,
+	CheckAccount,
+>;
+
+#[test]
+fn either_matcher_accepts_both_identifiers() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		let concrete: MultiAsset = (Here, 10 * CENTS).into();
+		let abstract_: MultiAsset = (b"KSM".to_vec(), 10 * CENTS).into();
+		let other: MultiAsset = (b"DOT".to_vec(), 10 * CENTS).into();
+		assert_eq!(MigratingAdapter::deposit_asset(&concrete, &para_location(PARA_ID)), Ok(()));
+		assert_eq!(MigratingAdapter::deposit_asset(&abstract_, &para_location(PARA_ID)), Ok(()));
+		assert_eq!(
+			MigratingAdapter::deposit_asset(&other, &para_location(PARA_ID)),
+			Err(XcmError::AssetNotFound)
+		);
+		assert_eq!(Balances::free_balance(para_account(PARA_ID)), 20 * CENTS);
+	});
+}
+
+#[test]
+fn reconciliation_reports_teleport_leaks() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		let id = Concrete(Here.into());
+		let dest: MultiLocation = Parachain(1000).into();
+		<Adapter as TransactAsset>::check_out(&dest, &(Here, 10 * CENTS).into());
+		assert_eq!(
+			<Adapter>::reconcile_checking_account(&id, 10 * CENTS),
+			Some(CheckingAccountDelta::Balanced)
+		);
+
+		// A teleport is checked in, but its deposit never happens, so the liability is unchanged.
+		<Adapter as TransactAsset>::check_in(&dest, &(Here, 4 * CENTS).into());
+		assert_eq!(
+			<Adapter>::reconcile_checking_account(&id, 10 * CENTS),
+			Some(CheckingAccountDelta::Deficit(4 * CENTS))
+		);
+
+		let _ = Balances::deposit_creating(&CheckAccount::get(), 5 * CENTS);
+		assert_eq!(
+			<Adapter>::reconcile_checking_account(&id, 10 * CENTS),
+			Some(CheckingAccountDelta::Surplus(CENTS))
+		);
+
+		assert_eq!(<UncheckedAdapter>::reconcile_checking_account(&id, 10 * CENTS), None);
+	});
+}
+
+#[test]
+fn reconciliation_uses_the_free_balance_of_the_asset_checking_account() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		let dest: MultiLocation = Parachain(1000).into();
+		let concrete = Concrete(Here.into());
+		let abstract_ = Abstract(b"KSM".to_vec());
+		SeparatelyCheckedAdapter::check_out(&dest, &(concrete.clone(), 10 * CENTS).into());
+		SeparatelyCheckedAdapter::check_out(&dest, &(abstract_.clone(), 5 * CENTS).into());
+		assert_eq!(
+			SeparatelyCheckedAdapter::reconcile_checking_account(&concrete, 10 * CENTS),
+			Some(CheckingAccountDelta::Balanced)
+		);
+		assert_eq!(
+			SeparatelyCheckedAdapter::reconcile_checking_account(&abstract_, 5 * CENTS),
+			Some(CheckingAccountDelta::Balanced)
+		);
+
+		// Reserved funds cannot be checked in, so they do not cover the liability.
+		assert!(Balances::reserve(&para_account(1), 2 * CENTS).is_ok());
+		assert_eq!(
+			SeparatelyCheckedAdapter::verify_teleport_invariant(&abstract_, 5 * CENTS),
+			Err(2 * CENTS)
+		);
+		assert_eq!(
+			SeparatelyCheckedAdapter::verify_teleport_invariant(&concrete, 10 * CENTS),
+			Ok(())
+		);
+	});
+}
+
+/// XCM amounts are expressed in tenths of the local unit.
+pub struct FinerUnits;
+impl CurrencyAdapterConfig<AccountId, Balances> for FinerUnits {
+	fn rate() -> (u128, u128) {
+		(1, 10)
+	}
+}
+
+/// XCM amounts are expressed in tens of the local unit.
+pub struct CoarserUnits;
+impl CurrencyAdapterConfig<AccountId, Balances> for CoarserUnits {
+	fn rate() -> (u128, u128) {
+		(10, 1)
+	}
+}
+
+#[test]
+fn teleport_invariant_reports_the_discrepancy() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		let id = Concrete(Here.into());
+		let dest: MultiLocation = Parachain(1000).into();
+		<Adapter as TransactAsset>::check_out(&dest, &(Here, 10 * CENTS).into());
+		assert_eq!(<Adapter>::verify_teleport_invariant(&id, 10 * CENTS), Ok(()));
+		assert_eq!(<Adapter>::verify_teleport_invariant(&id, 12 * CENTS), Err(2 * CENTS));
+		assert_eq!(<Adapter>::verify_teleport_invariant(&id, 7 * CENTS), Err(3 * CENTS));
+
+		// Without a checking account no liability can be covered.
+		assert_eq!(<UncheckedAdapter>::verify_teleport_invariant(&id, 0), Ok(()));
+		assert_eq!(<UncheckedAdapter>::verify_teleport_invariant(&id, CENTS), Err(CENTS));
+	});
+}
+
+#[test]
+fn amounts_are_scaled_by_the_rate() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(Adapter::<CoarserUnits>::deposit_asset(&asset, &para_location(PARA_ID)), Ok(()));
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE + 100 * CENTS);
+
+		assert_eq!(
+			Adapter::<FinerUnits>::withdraw_asset(&asset, &para_location(PARA_ID)),
+			Ok((Here, 10 * CENTS).into())
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE + 99 * CENTS);
+	});
+}
+
+#[test]
+fn scaled_amounts_round_down() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), INITIAL_BALANCE)]).execute_with(|| {
+		// Only the whole local units are withdrawn, and reported back in XCM units.
+		let asset: MultiAsset = (Here, 105).into();
+		assert_eq!(
+			Adapter::<FinerUnits>::withdraw_asset(&asset, &para_location(PARA_ID)),
+			Ok((Here, 100).into())
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE - 10);
+
+		// Less than one local unit is nothing at all.
+		let asset: MultiAsset = (Here, 9).into();
+		assert_eq!(
+			Adapter::<FinerUnits>::withdraw_asset(&asset, &para_location(PARA_ID)).map(|_| ()),
+			Err(XcmError::FailedToTransactAsset("ZeroAmount"))
+		);
+	});
+}
+
+/// As `FinerUnits`, but rounding fee withdrawals up.
+pub struct FinerUnitsRoundingUp;
+impl CurrencyAdapterConfig<AccountId, Balances> for FinerUnitsRoundingUp {
+	const WITHDRAW_ROUNDING: Rounding = Rounding::Up;
+	fn rate() -> (u128, u128) {
+		(1, 10)
+	}
+}
+
+#[test]
+fn fee_withdrawals_can_round_up() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), INITIAL_BALANCE)]).execute_with(|| {
+		// The fee is never undercharged, and the whole local units taken are reported back.
+		let fee: MultiAsset = (Here, 105).into();
+		assert_eq!(
+			Adapter::<FinerUnitsRoundingUp>::withdraw_asset(&fee, &para_location(PARA_ID)),
+			Ok((Here, 110).into())
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE - 11);
+
+		// Even less than one local unit costs one.
+		let fee: MultiAsset = (Here, 9).into();
+		assert_eq!(
+			Adapter::<FinerUnitsRoundingUp>::withdraw_asset(&fee, &para_location(PARA_ID)),
+			Ok((Here, 10).into())
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE - 12);
+
+		// Batch withdrawals round the same way.
+		let fees: MultiAssets = vec![(Here, 9).into()].into();
+		assert_eq!(
+			Adapter::<FinerUnitsRoundingUp>::withdraw_assets(&fees, &para_location(PARA_ID)),
+			Ok((Here, 10).into())
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE - 13);
+
+		// Deposits still round down.
+		assert_eq!(
+			Adapter::<FinerUnitsRoundingUp>::deposit_asset(
+				&(Here, 105).into(),
+				&para_location(PARA_ID)
+			),
+			Ok(())
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE - 3);
+	});
+}
+
+/// The abstract KSM has 12 decimals elsewhere, the concrete one 10 like the local currency.
+pub struct TwelveDecimalsAbstract;
+impl CurrencyAdapterConfig<AccountId, Balances> for TwelveDecimalsAbstract {
+	fn rate_for(what: &MultiAsset) -> Option<(u128, u128)> {
+		match what.id {
+			Abstract(_) => decimals_rate(12, 10),
+			Concrete(_) => Some(Self::rate()),
+		}
+	}
+}
+
+/// The abstract KSM has 8 decimals elsewhere.
+pub struct EightDecimalsAbstract;
+impl CurrencyAdapterConfig<AccountId, Balances> for EightDecimalsAbstract {
+	fn rate_for(what: &MultiAsset) -> Option<(u128, u128)> {
+		match what.id {
+			Abstract(_) => decimals_rate(8, 10),
+			Concrete(_) => Some(Self::rate()),
+		}
+	}
+}
+
+/// The abstract KSM has more decimals elsewhere than a `u128` can scale.
+pub struct TooManyDecimalsAbstract;
+impl CurrencyAdapterConfig<AccountId, Balances> for TooManyDecimalsAbstract {
+	fn rate_for(what: &MultiAsset) -> Option<(u128, u128)> {
+		match what.id {
+			Abstract(_) => decimals_rate(50, 10),
+			Concrete(_) => Some(Self::rate()),
+		}
+	}
+}
+
+type DecimalsAdapter<Config> = CurrencyAdapter<
+	Balances,
+	EitherMatcher<IsConcrete<KsmLocation>, IsAbstract<KsmName>>,
+	SovereignAccountOf,
+	AccountId,
+	CheckAccount,
+	Config,
+>;
+
+#[test]
+fn amounts_are_normalized_to_local_decimals() {
+	assert_eq!(decimals_rate(12, 10), Some((1, 100)));
+	assert_eq!(decimals_rate(10, 12), Some((100, 1)));
+	assert_eq!(decimals_rate(10, 10), Some((1, 1)));
+
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		// Twelve decimals lose the two lowest digits.
+		let remote: MultiAsset = (b"KSM".to_vec(), 10 * CENTS * 100 + 99).into();
+		assert_eq!(
+			DecimalsAdapter::<TwelveDecimalsAbstract>::deposit_asset(
+				&remote,
+				&para_location(PARA_ID)
+			),
+			Ok(())
+		);
+		assert_eq!(Balances::free_balance(&who), 10 * CENTS);
+		assert_eq!(
+			DecimalsAdapter::<TwelveDecimalsAbstract>::withdraw_asset(
+				&(b"KSM".to_vec(), CENTS * 100).into(),
+				&para_location(PARA_ID)
+			),
+			Ok((b"KSM".to_vec(), CENTS * 100).into())
+		);
+		assert_eq!(Balances::free_balance(&who), 9 * CENTS);
+
+		// Less than one local unit is nothing at all.
+		assert_eq!(
+			DecimalsAdapter::<TwelveDecimalsAbstract>::deposit_asset(
+				&(b"KSM".to_vec(), 99).into(),
+				&para_location(PARA_ID)
+			),
+			Err(XcmError::FailedToTransactAsset("ZeroAmount"))
+		);
+
+		// Eight decimals gain two, and the concrete asset is left as it is.
+		assert_eq!(
+			DecimalsAdapter::<EightDecimalsAbstract>::deposit_asset(
+				&(b"KSM".to_vec(), CENTS).into(),
+				&para_location(PARA_ID)
+			),
+			Ok(())
+		);
+		assert_eq!(Balances::free_balance(&who), 109 * CENTS);
+		assert_eq!(
+			DecimalsAdapter::<EightDecimalsAbstract>::deposit_asset(
+				&(Here, CENTS).into(),
+				&para_location(PARA_ID)
+			),
+			Ok(())
+		);
+		assert_eq!(Balances::free_balance(&who), 110 * CENTS);
+
+		// Amounts which overflow once scaled are rejected.
+		assert_eq!(
+			DecimalsAdapter::<EightDecimalsAbstract>::deposit_asset(
+				&(b"KSM".to_vec(), u128::MAX / 10).into(),
+				&para_location(PARA_ID)
+			),
+			Err(XcmError::FailedToTransactAsset("AmountToBalanceConversionFailed"))
+		);
+
+		// So are assets whose decimals differ by more than a `u128` can scale.
+		assert_eq!(decimals_rate(0, 38), Some((10u128.pow(38), 1)));
+		assert_eq!(decimals_rate(0, 39), None);
+		assert_eq!(decimals_rate(39, 0), None);
+		assert_eq!(
+			DecimalsAdapter::<TooManyDecimalsAbstract>::deposit_asset(
+				&(b"KSM".to_vec(), CENTS).into(),
+				&para_location(PARA_ID)
+			),
+			Err(XcmError::FailedToTransactAsset("AmountToBalanceConversionFailed"))
+		);
+		assert_eq!(Balances::free_balance(&who), 110 * CENTS);
+	});
+}
+
+pub struct OverflowingRate;
+impl CurrencyAdapterConfig<AccountId, Balances> for OverflowingRate {
+	fn rate() -> (u128, u128) {
+		(u128::MAX, 1)
+	}
+}
+
+#[test]
+fn scaling_overflow_is_a_conversion_failure() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		let asset: MultiAsset = (Here, 2).into();
+		let conversion_failed =
+			Err(XcmError::FailedToTransactAsset("AmountToBalanceConversionFailed"));
+		assert_eq!(
+			Adapter::<OverflowingRate>::deposit_asset(&asset, &para_location(PARA_ID)),
+			conversion_failed
+		);
+		assert_eq!(
+			Adapter::<OverflowingRate>::can_check_out(&Parent.into(), &asset),
+			conversion_failed
+		);
+	});
+}
+
+/// Rounds withdrawals up to a local amount whose XCM equivalent exceeds `u128`.
+pub struct OverflowingWithdrawal;
+impl CurrencyAdapterConfig<AccountId, Balances> for OverflowingWithdrawal {
+	const WITHDRAW_ROUNDING: Rounding = Rounding::Up;
+	fn rate() -> (u128, u128) {
+		(2, u128::MAX / 2 + 1)
+	}
+}
+
+#[test]
+fn withdrawn_amount_which_cannot_be_reported_is_a_conversion_failure() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, u128::MAX / 2).into();
+		assert_eq!(
+			Adapter::<OverflowingWithdrawal>::withdraw_asset(&asset, &para_location(PARA_ID))
+				.map(|_| ()),
+			Err(XcmError::FailedToTransactAsset("AmountToBalanceConversionFailed"))
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE);
+	});
+}
+
+type NarrowAdapter<Config = ()> = CurrencyAdapter<
+	NarrowBalances,
+	IsConcrete<KsmLocation>,
+	SovereignAccountOf,
+	AccountId,
+	(),
+	Config,
+>;
+
+pub struct SaturateAmounts;
+impl CurrencyAdapterConfig<AccountId, NarrowBalances> for SaturateAmounts {
+	const AMOUNT_CONVERSION: AmountConversion = AmountConversion::Saturate;
+}
+
+#[test]
+fn amounts_exceeding_the_balance_type_follow_the_conversion_policy() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		let huge: MultiAsset = (Here, u64::MAX as u128 + 1).into();
+		let conversion_failed =
+			Err(XcmError::FailedToTransactAsset("AmountToBalanceConversionFailed"));
+		assert_eq!(
+			NarrowAdapter::<()>::deposit_asset(&huge, &para_location(PARA_ID)),
+			conversion_failed
+		);
+		assert_eq!(NarrowBalances::free_balance(&who), 0);
+		assert_eq!(
+			NarrowAdapter::<SaturateAmounts>::deposit_asset(&huge, &para_location(PARA_ID)),
+			Ok(())
+		);
+		assert_eq!(NarrowBalances::free_balance(&who), u64::MAX);
+
+		assert_eq!(
+			NarrowAdapter::<()>::withdraw_asset(&huge, &para_location(PARA_ID)).map(|_| ()),
+			conversion_failed
+		);
+		assert_eq!(NarrowBalances::free_balance(&who), u64::MAX);
+		assert_eq!(
+			NarrowAdapter::<SaturateAmounts>::withdraw_asset(&huge, &para_location(PARA_ID)),
+			Ok(MultiAsset::from((Here, u64::MAX as u128)).into())
+		);
+		assert_eq!(NarrowBalances::free_balance(&who), 0);
+	});
+}
+
+thread_local! {
+	static NONCES: RefCell<BTreeSet<u64>> = RefCell::new(BTreeSet::new());
+}
+
+pub struct ReplayGuard;
+impl CurrencyAdapterConfig<AccountId, Balances> for ReplayGuard {
+	fn note_nonce(nonce: u64) -> bool {
+		NONCES.with(|n| n.borrow_mut().insert(nonce))
+	}
+}
+
+#[test]
+fn replayed_teleports_are_checked_in_and_out_once() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		let dest: MultiLocation = Parachain(1000).into();
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		Adapter::<ReplayGuard>::check_out_once(&dest, &asset, 1);
+		Adapter::<ReplayGuard>::check_out_once(&dest, &asset, 1);
+		assert_eq!(Balances::free_balance(CheckAccount::get()), 10 * CENTS);
+
+		let asset: MultiAsset = (Here, 4 * CENTS).into();
+		Adapter::<ReplayGuard>::check_in_once(&dest, &asset, 2);
+		Adapter::<ReplayGuard>::check_in_once(&dest, &asset, 2);
+		assert_eq!(Balances::free_balance(CheckAccount::get()), 6 * CENTS);
+
+		// Without a guard every call counts.
+		<Adapter>::check_in_once(&dest, &asset, 2);
+		assert_eq!(Balances::free_balance(CheckAccount::get()), 2 * CENTS);
+	});
+}
+
+#[test]
+fn batch_withdrawal_is_all_or_nothing() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), INITIAL_BALANCE)]).execute_with(|| {
+		let concrete: MultiAsset = (Here, 10 * CENTS).into();
+		let abstract_: MultiAsset = (b"KSM".to_vec(), 5 * CENTS).into();
+		let unknown: MultiAsset = (b"ZZZ".to_vec(), CENTS).into();
+
+		let assets: MultiAssets = vec![concrete.clone(), abstract_.clone(), unknown].into();
+		assert_eq!(
+			MigratingAdapter::withdraw_assets(&assets, &para_location(PARA_ID)),
+			Err(XcmError::AssetNotFound)
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE);
+
+		let assets: MultiAssets = vec![concrete.clone(), abstract_.clone()].into();
+		assert_eq!(
+			MigratingAdapter::withdraw_assets(&assets, &para_location(PARA_ID)),
+			Ok(vec![concrete, abstract_].into())
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE - 15 * CENTS);
+	});
+}
+
+/// Records failures in storage, where a rolled back transaction would discard them.
+pub struct StoredFailures;
+impl CurrencyAdapterConfig<AccountId, Balances> for StoredFailures {
+	fn on_failure(_: &MultiAsset, _: &MultiLocation, _: &XcmError) {
+		let failures = frame_support::storage::unhashed::get_or_default::<u32>(b"failures");
+		frame_support::storage::unhashed::put(b"failures", &(failures + 1));
+	}
+}
+
+#[test]
+fn failures_are_reported_after_the_rollback() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), INITIAL_BALANCE)]).execute_with(|| {
+		let failures = || frame_support::storage::unhashed::get_or_default::<u32>(b"failures");
+		let some: MultiAsset = (Here, 10 * CENTS).into();
+		let too_much: MultiAsset = (Here, INITIAL_BALANCE).into();
+
+		let assets: MultiAssets = vec![some.clone(), too_much.clone()].into();
+		assert!(
+			Adapter::<StoredFailures>::withdraw_assets(&assets, &para_location(PARA_ID)).is_err()
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE);
+		assert_eq!(failures(), 1);
+
+		assert!(Adapter::<StoredFailures>::withdraw_asset_with_change(
+			&some,
+			&too_much,
+			&para_location(PARA_ID)
+		)
+		.is_err());
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE);
+		assert_eq!(failures(), 2);
+
+		assert!(Adapter::<StoredFailures>::transfer_asset(
+			&too_much,
+			&para_location(PARA_ID),
+			&para_location(3000)
+		)
+		.is_err());
+		assert_eq!(failures(), 3);
+	});
+}
+
+pub struct DrainableCheckingAccount;
+impl CurrencyAdapterConfig<AccountId, Balances> for DrainableCheckingAccount {
+	const CHECKING_ACCOUNT_EXISTENCE: ExistenceRequirement = ExistenceRequirement::AllowDeath;
+}
+
+#[test]
+fn checking_account_is_drained_only_if_allowed() {
+	kusama_like_with_balances(vec![(CheckAccount::get(), 10 * CENTS)]).execute_with(|| {
+		let dest: MultiLocation = Parachain(1000).into();
+		let everything: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(
+			<Adapter as TransactAsset>::can_check_in(&dest, &everything),
+			Err(XcmError::NotWithdrawable)
+		);
+		let all_but_minimum: MultiAsset = (Here, 9 * CENTS).into();
+		assert_eq!(<Adapter as TransactAsset>::can_check_in(&dest, &all_but_minimum), Ok(()));
+
+		assert_eq!(Adapter::<DrainableCheckingAccount>::can_check_in(&dest, &everything), Ok(()));
+		Adapter::<DrainableCheckingAccount>::check_in(&dest, &everything);
+		assert_eq!(Balances::total_balance(&CheckAccount::get()), 0);
+	});
+}
+
+pub struct IgnoreUnknownAssets;
+impl CurrencyAdapterConfig<AccountId, Balances> for IgnoreUnknownAssets {
+	const UNKNOWN_ASSET_POLICY: UnknownAssetPolicy = UnknownAssetPolicy::Ignore;
+}
+
+#[test]
+fn unknown_assets_are_rejected_or_ignored() {
+	kusama_like_with_balances(vec![(para_account(PARA_ID), INITIAL_BALANCE)]).execute_with(|| {
+		let unknown: MultiAsset = (Parent, 10 * CENTS).into();
+		assert_eq!(
+			<Adapter as TransactAsset>::deposit_asset(&unknown, &para_location(PARA_ID)),
+			Err(XcmError::AssetNotFound)
+		);
+		assert_eq!(
+			<Adapter as TransactAsset>::withdraw_asset(&unknown, &para_location(PARA_ID)),
+			Err(XcmError::AssetNotFound)
+		);
+
+		assert_eq!(
+			Adapter::<IgnoreUnknownAssets>::deposit_asset(&unknown, &para_location(PARA_ID)),
+			Ok(())
+		);
+		assert_eq!(
+			Adapter::<IgnoreUnknownAssets>::withdraw_asset(&unknown, &para_location(PARA_ID)),
+			Ok(Assets::new())
+		);
+		assert_eq!(Balances::free_balance(para_account(PARA_ID)), INITIAL_BALANCE);
+
+		// Batch withdrawals skip them as well.
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		let assets: MultiAssets = vec![asset.clone(), unknown].into();
+		assert_eq!(
+			Adapter::<IgnoreUnknownAssets>::withdraw_assets(&assets, &para_location(PARA_ID)),
+			Ok(asset.clone().into())
+		);
+		assert_eq!(Balances::free_balance(para_account(PARA_ID)), INITIAL_BALANCE - 10 * CENTS);
+
+		// Other failures are still reported.
+		assert_eq!(
+			Adapter::<IgnoreUnknownAssets>::withdraw_asset(&asset, &para_location(3000))
+				.map(|_| ()),
+			Err(XcmError::FailedToTransactAsset("InsufficientBalance"))
+		);
+	});
+}
+
+#[test]
+fn over_withdrawal_returns_change() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), INITIAL_BALANCE)]).execute_with(|| {
+		let requested: MultiAsset = (Here, 10 * CENTS).into();
+		let needed: MultiAsset = (Here, 7 * CENTS).into();
+		let (consumed, change) =
+			<Adapter>::withdraw_asset_with_change(&requested, &needed, &para_location(PARA_ID))
+				.unwrap();
+		assert_eq!(consumed, MultiAsset::from((Here, 7 * CENTS)).into());
+		assert_eq!(change, MultiAsset::from((Here, 3 * CENTS)).into());
+		let mut total = consumed;
+		total.subsume_assets(change);
+		assert_eq!(total, requested.into());
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE - 10 * CENTS);
+
+		// If more is needed than requested, the needed amount is withdrawn without change.
+		let requested: MultiAsset = (Here, 5 * CENTS).into();
+		assert_eq!(
+			<Adapter>::withdraw_asset_with_change(&requested, &needed, &para_location(PARA_ID)),
+			Ok((needed.clone().into(), Assets::new()))
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE - 17 * CENTS);
+
+		let other: MultiAsset = (Parent, 7 * CENTS).into();
+		assert_eq!(
+			<Adapter>::withdraw_asset_with_change(&requested, &other, &para_location(PARA_ID)),
+			Err(XcmError::FailedToTransactAsset("ChangeMismatch"))
+		);
+
+		// A withdrawal which falls short of the need is rolled back.
+		let needed: MultiAsset = (Here, 10 * CENTS + 1).into();
+		assert_eq!(
+			RoundingAdapter::withdraw_asset_with_change(
+				&requested,
+				&needed,
+				&para_location(PARA_ID)
+			),
+			Err(XcmError::FailedToTransactAsset("NeedNotCovered"))
+		);
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE - 17 * CENTS);
+	});
+}
+
+thread_local! {
+	static REGISTERED: RefCell<Vec<MultiLocation>> = RefCell::new(Vec::new());
+}
+
+pub struct TestRegistry;
+impl AssetRegistry for TestRegistry {
+	fn is_registered(location: &MultiLocation) -> bool {
+		REGISTERED.with(|r| r.borrow().contains(location))
+	}
+}
+
+type RegistryAdapter = CurrencyAdapter<
+	Balances,
+	IsRegistered<TestRegistry>,
+	SovereignAccountOf,
+	AccountId,
+	CheckAccount,
+>;
+
+#[test]
+fn assets_registered_at_runtime_are_transacted() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		let location: MultiLocation = (Parent, GeneralIndex(1)).into();
+		let asset: MultiAsset = (location.clone(), 10 * CENTS).into();
+		assert_eq!(
+			RegistryAdapter::deposit_asset(&asset, &para_location(PARA_ID)),
+			Err(XcmError::AssetNotFound)
+		);
+
+		REGISTERED.with(|r| r.borrow_mut().push(location));
+		assert_eq!(RegistryAdapter::deposit_asset(&asset, &para_location(PARA_ID)), Ok(()));
+		assert_eq!(Balances::free_balance(para_account(PARA_ID)), 10 * CENTS);
+	});
+}
+
+#[test]
+fn can_check_in_reports_unknown_assets_before_checking_account_problems() {
+	kusama_like_with_balances(vec![(CheckAccount::get(), 5 * CENTS)]).execute_with(|| {
+		capture_logs();
+		let unknown: MultiAsset = (Parent, 10 * CENTS).into();
+		assert_eq!(
+			<Adapter as TransactAsset>::can_check_in(&Parent.into(), &unknown),
+			Err(XcmError::AssetNotFound)
+		);
+		assert_eq!(
+			UncheckedAdapter::<RequireCheckingAccount>::can_check_in(&Parent.into(), &unknown),
+			Err(XcmError::AssetNotFound)
+		);
+
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(
+			<Adapter as TransactAsset>::can_check_in(&Parent.into(), &asset),
+			Err(XcmError::NotWithdrawable)
+		);
+		assert!(logged(&format!(
+			"insufficient balance: required: {:?}, available: {:?}",
+			10 * CENTS,
+			5 * CENTS
+		)));
+	});
+}
+
+/// Tracks teleports of the abstract representation in a checking account of its own.
+pub struct SeparateCheckingAccounts;
+impl CurrencyAdapterConfig<AccountId, Balances> for SeparateCheckingAccounts {
+	fn checking_account_for(what: &MultiAsset) -> Option<AccountId> {
+		match what.id {
+			Abstract(_) => Some(para_account(1)),
+			Concrete(_) => None,
+		}
+	}
+}
+
+type SeparatelyCheckedAdapter = CurrencyAdapter<
+	Balances,
+	EitherMatcher<IsConcrete<KsmLocation>, IsAbstract<KsmName>>,
+	SovereignAccountOf,
+	AccountId,
+	CheckAccount,
+	SeparateCheckingAccounts,
+>;
+
+#[test]
+fn assets_can_have_their_own_checking_accounts() {
+	kusama_like_with_balances(vec![(CheckAccount::get(), CENTS), (para_account(1), CENTS)])
+		.execute_with(|| {
+			let dest: MultiLocation = Parachain(1000).into();
+			let concrete: MultiAsset = (Here, 10 * CENTS).into();
+			let abstract_: MultiAsset = (b"KSM".to_vec(), 5 * CENTS).into();
+			SeparatelyCheckedAdapter::check_out(&dest, &concrete);
+			SeparatelyCheckedAdapter::check_out(&dest, &abstract_);
+			assert_eq!(Balances::free_balance(CheckAccount::get()), 11 * CENTS);
+			assert_eq!(Balances::free_balance(para_account(1)), 6 * CENTS);
+
+			// The liability of one asset cannot cover teleports of the other.
+			let too_much: MultiAsset = (b"KSM".to_vec(), 10 * CENTS).into();
+			assert_eq!(
+				SeparatelyCheckedAdapter::can_check_in(&dest, &too_much),
+				Err(XcmError::NotWithdrawable)
+			);
+			SeparatelyCheckedAdapter::check_in(&dest, &abstract_);
+			assert_eq!(Balances::free_balance(CheckAccount::get()), 11 * CENTS);
+			assert_eq!(Balances::free_balance(para_account(1)), CENTS);
+		});
+}
+
+#[test]
+fn missing_checking_account_is_flagged_in_both_directions_in_strict_mode() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		capture_logs();
+		let dest: MultiLocation = Parachain(1000).into();
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(<UncheckedAdapter as TransactAsset>::can_check_out(&dest, &asset), Ok(()));
+		<UncheckedAdapter as TransactAsset>::check_out(&dest, &asset);
+		assert!(!logged("without checking account"));
+
+		assert_eq!(
+			UncheckedAdapter::<RequireCheckingAccount>::can_check_out(&dest, &asset),
+			Err(XcmError::FailedToTransactAsset("NoCheckingAccount"))
+		);
+		UncheckedAdapter::<RequireCheckingAccount>::check_out(&dest, &asset);
+		assert!(logged("check_out without checking account"));
+		UncheckedAdapter::<RequireCheckingAccount>::check_in(&dest, &asset);
+		assert!(logged("check_in without checking account"));
+	});
+}
+
+/// Allows only the parachain `PARA_ID`.
+pub struct OnlyOurPara;
+impl Contains<MultiLocation> for OnlyOurPara {
+	fn contains(location: &MultiLocation) -> bool {
+		location == &para_location(PARA_ID)
+	}
+}
+
+type GuardedAdapter = AllowedLocations<Adapter, IsConcrete<KsmLocation>, OnlyOurPara>;
+
+#[test]
+fn only_allowed_locations_are_transacted() {
+	kusama_like_with_balances(vec![(para_account(PARA_ID), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		let not_allowed = XcmError::FailedToTransactAsset("LocationNotAllowed");
+		assert_eq!(GuardedAdapter::deposit_asset(&asset, &para_location(PARA_ID)), Ok(()));
+		assert_eq!(
+			GuardedAdapter::deposit_asset(&asset, &para_location(3000)),
+			Err(not_allowed.clone())
+		);
+		assert_eq!(
+			GuardedAdapter::withdraw_asset(&asset, &para_location(PARA_ID)),
+			Ok(asset.clone().into())
+		);
+		assert_eq!(
+			GuardedAdapter::transfer_asset(&asset, &para_location(PARA_ID), &para_location(3000)),
+			Err(not_allowed.clone())
+		);
+		assert_eq!(Balances::free_balance(para_account(3000)), 0);
+
+		assert_eq!(GuardedAdapter::can_check_out(&para_location(PARA_ID), &asset), Ok(()));
+		assert_eq!(
+			GuardedAdapter::can_check_out(&para_location(3000), &asset),
+			Err(not_allowed.clone())
+		);
+		assert_eq!(GuardedAdapter::can_check_in(&para_location(3000), &asset), Err(not_allowed));
+	});
+}
+
+#[test]
+fn assets_of_other_transactors_pass_the_location_guard() {
+	kusama_like_with_balances(vec![]).execute_with(|| {
+		type AbstractAdapter = CurrencyAdapter<
+			Balances,
+			IsAbstract<KsmName>,
+			SovereignAccountOf,
+			AccountId,
+			CheckAccount,
+		>;
+		let asset: MultiAsset = (b"KSM".to_vec(), 10 * CENTS).into();
+		assert_eq!(
+			GuardedAdapter::deposit_asset(&asset, &para_location(3000)),
+			Err(XcmError::AssetNotFound)
+		);
+		assert_eq!(
+			<(GuardedAdapter, AbstractAdapter)>::deposit_asset(&asset, &para_location(3000)),
+			Ok(())
+		);
+		assert_eq!(Balances::free_balance(para_account(3000)), 10 * CENTS);
+		assert_eq!(
+			<(GuardedAdapter, AbstractAdapter)>::can_check_out(&para_location(3000), &asset),
+			Ok(())
+		);
+	});
+}
+
+frame_support::parameter_types! {
+	pub const XcmLockId: LockIdentifier = *b"xcm_lock";
+}
+
+/// Reads the locks of the balances pallet.
+pub struct BalancesLocks;
+impl InspectLock<AccountId, Balance> for BalancesLocks {
+	fn locked(id: LockIdentifier, who: &AccountId) -> Balance {
+		Balances::locks(who).iter().find(|l| l.id == id).map_or(0, |l| l.amount)
+	}
+}
+
+type LockingAdapter = LockingCurrencyAdapter<
+	Balances,
+	IsConcrete<KsmLocation>,
+	SovereignAccountOf,
+	AccountId,
+	XcmLockId,
+	BalancesLocks,
+>;
+
+#[test]
+fn locked_assets_are_unlocked_by_a_deposit() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(
+			LockingAdapter::withdraw_asset(&asset, &para_location(PARA_ID)),
+			Ok(asset.clone().into())
+		);
+		// Nothing is burnt, but the amount can no longer be spent.
+		assert_eq!(Balances::free_balance(&who), INITIAL_BALANCE);
+		assert_eq!(Balances::total_issuance(), INITIAL_BALANCE);
+		assert_eq!(Balances::usable_balance(&who), INITIAL_BALANCE - 10 * CENTS);
+
+		assert_eq!(LockingAdapter::deposit_asset(&asset, &para_location(PARA_ID)), Ok(()));
+		assert_eq!(Balances::usable_balance(&who), INITIAL_BALANCE);
+		assert_eq!(BalancesLocks::locked(XcmLockId::get(), &who), 0);
+
+		// More than the free balance cannot be locked.
+		let too_much: MultiAsset = (Here, INITIAL_BALANCE + 1).into();
+		assert_eq!(
+			LockingAdapter::withdraw_asset(&too_much, &para_location(PARA_ID)),
+			Err(XcmError::FailedToTransactAsset("InsufficientBalance"))
+		);
+		assert_eq!(Balances::usable_balance(&who), INITIAL_BALANCE);
+	});
+}
+
+#[test]
+fn repeated_withdrawals_add_to_the_lock() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), 30 * CENTS)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert!(LockingAdapter::withdraw_asset(&asset, &para_location(PARA_ID)).is_ok());
+		assert!(LockingAdapter::withdraw_asset(&asset, &para_location(PARA_ID)).is_ok());
+		assert_eq!(BalancesLocks::locked(XcmLockId::get(), &who), 20 * CENTS);
+		assert_eq!(Balances::usable_balance(&who), 10 * CENTS);
+
+		// Funds already locked cannot be withdrawn again.
+		let rest: MultiAsset = (Here, 20 * CENTS).into();
+		assert_eq!(
+			LockingAdapter::withdraw_asset(&rest, &para_location(PARA_ID)).map(|_| ()),
+			Err(XcmError::FailedToTransactAsset("BalanceLocked"))
+		);
+		assert_eq!(BalancesLocks::locked(XcmLockId::get(), &who), 20 * CENTS);
+	});
+}
+
+#[test]
+fn funds_held_by_other_locks_cannot_be_withdrawn() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), 30 * CENTS)]).execute_with(|| {
+		Balances::set_lock(*b"staking ", &who, 25 * CENTS, WithdrawReasons::all());
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(
+			LockingAdapter::withdraw_asset(&asset, &para_location(PARA_ID)).map(|_| ()),
+			Err(XcmError::FailedToTransactAsset("BalanceLocked"))
+		);
+		assert_eq!(BalancesLocks::locked(XcmLockId::get(), &who), 0);
+
+		let usable: MultiAsset = (Here, 5 * CENTS).into();
+		assert!(LockingAdapter::withdraw_asset(&usable, &para_location(PARA_ID)).is_ok());
+		assert_eq!(BalancesLocks::locked(XcmLockId::get(), &who), 5 * CENTS);
+	});
+}
+
+#[test]
+fn deposits_unlock_only_their_amount() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert!(LockingAdapter::withdraw_asset(&asset, &para_location(PARA_ID)).is_ok());
+
+		let part: MultiAsset = (Here, CENTS).into();
+		assert_eq!(LockingAdapter::deposit_asset(&part, &para_location(PARA_ID)), Ok(()));
+		assert_eq!(BalancesLocks::locked(XcmLockId::get(), &who), 9 * CENTS);
+		assert_eq!(Balances::usable_balance(&who), INITIAL_BALANCE - 9 * CENTS);
+
+		// More than is locked cannot be deposited.
+		assert_eq!(
+			LockingAdapter::deposit_asset(&asset, &para_location(PARA_ID)),
+			Err(XcmError::FailedToTransactAsset("NotLocked"))
+		);
+		assert_eq!(BalancesLocks::locked(XcmLockId::get(), &who), 9 * CENTS);
+	});
+}
+
+#[test]
+fn deposit_into_an_account_without_a_lock_fails() {
+	kusama_like_with_balances(vec![(para_account(PARA_ID), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert_eq!(
+			LockingAdapter::deposit_asset(&asset, &para_location(3000)),
+			Err(XcmError::FailedToTransactAsset("NotLocked"))
+		);
+		assert_eq!(Balances::free_balance(para_account(3000)), 0);
+	});
+}
+
+#[test]
+fn locked_assets_cannot_be_transferred() {
+	let who = para_account(PARA_ID);
+	kusama_like_with_balances(vec![(who.clone(), INITIAL_BALANCE)]).execute_with(|| {
+		let asset: MultiAsset = (Here, 10 * CENTS).into();
+		assert!(LockingAdapter::withdraw_asset(&asset, &para_location(3000)).is_err());
+		assert!(LockingAdapter::withdraw_asset(&asset, &para_location(PARA_ID)).is_ok());
+		assert_eq!(
+			LockingAdapter::beam_asset(&asset, &para_location(PARA_ID), &para_location(3000))
+				.map(|_| ()),
+			Err(XcmError::FailedToTransactAsset("TransferNotSupported"))
+		);
+		assert_eq!(BalancesLocks::locked(XcmLockId::get(), &who), 10 * CENTS);
+	});
+}
@@ -106,6 +106,24 @@ impl pallet_balances::Config for Runtime {
 	type ReserveIdentifier = [u8; 8];
 }
 
+/// Balances with a `u64` balance type, narrower than XCM amounts.
+impl pallet_balances::Config<pallet_balances::Instance1> for Runtime {
+	type MaxLocks = MaxLocks;
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = frame_support::traits::ConstU64<1>;
+	type AccountStore = frame_support::traits::StorageMapShim<
+		pallet_balances::Account<Runtime, pallet_balances::Instance1>,
+		frame_system::Provider<Runtime>,
+		AccountId,
+		pallet_balances::AccountData<u64>,
+	>;
+	type WeightInfo = ();
+	type MaxReserves = MaxReserves;
+	type ReserveIdentifier = [u8; 8];
+}
+
 impl shared::Config for Runtime {}
 
 impl configuration::Config for Runtime {
@@ -208,6 +226,7 @@ construct_runtime!(
 	{
 		System: frame_system::{Pallet, Call, Storage, Config, Event<T>},
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		NarrowBalances: pallet_balances::<Instance1>::{Pallet, Storage, Event<T>},
 		ParasOrigin: origin::{Pallet, Origin},
 		XcmPallet: pallet_xcm::{Pallet, Call, Storage, Event<T>, Origin},
 	}
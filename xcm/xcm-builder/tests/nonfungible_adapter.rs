@@ -0,0 +1,135 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use frame_support::{
+	parameter_types,
+	traits::tokens::nonfungible::{Inspect, Mutate, Transfer},
+};
+use polkadot_parachain::primitives::Id as ParaId;
+use sp_runtime::{traits::AccountIdConversion, AccountId32, DispatchError, DispatchResult};
+use sp_std::{
+	cell::RefCell,
+	collections::btree_map::{BTreeMap, Entry},
+};
+use xcm::latest::prelude::*;
+use xcm_builder::{ChildParachainConvertsVia, IsConcrete, NonFungibleAdapter};
+use xcm_executor::{traits::TransactAsset, Assets};
+
+type AccountId = AccountId32;
+
+thread_local! {
+	static OWNERS: RefCell<BTreeMap<AssetInstance, AccountId>> = RefCell::new(BTreeMap::new());
+}
+
+/// A single collection of unique instances, kept in memory.
+pub struct TestCollection;
+impl Inspect<AccountId> for TestCollection {
+	type InstanceId = AssetInstance;
+
+	fn owner(instance: &AssetInstance) -> Option<AccountId> {
+		OWNERS.with(|o| o.borrow().get(instance).cloned())
+	}
+}
+impl Mutate<AccountId> for TestCollection {
+	fn mint_into(instance: &AssetInstance, who: &AccountId) -> DispatchResult {
+		OWNERS.with(|o| match o.borrow_mut().entry(instance.clone()) {
+			Entry::Occupied(_) => Err(DispatchError::Other("AlreadyMinted")),
+			Entry::Vacant(e) => {
+				e.insert(who.clone());
+				Ok(())
+			},
+		})
+	}
+
+	fn burn_from(instance: &AssetInstance) -> DispatchResult {
+		OWNERS.with(|o| o.borrow_mut().remove(instance).map(|_| ()).ok_or("Unknown".into()))
+	}
+}
+impl Transfer<AccountId> for TestCollection {
+	fn transfer(instance: &AssetInstance, destination: &AccountId) -> DispatchResult {
+		OWNERS.with(|o| match o.borrow_mut().get_mut(instance) {
+			Some(owner) => {
+				*owner = destination.clone();
+				Ok(())
+			},
+			None => Err("Unknown".into()),
+		})
+	}
+}
+
+parameter_types! {
+	pub CollectionLocation: MultiLocation = PalletInstance(42).into();
+}
+
+type Adapter = NonFungibleAdapter<
+	TestCollection,
+	IsConcrete<CollectionLocation>,
+	ChildParachainConvertsVia<ParaId, AccountId>,
+	AccountId,
+>;
+
+fn para_location(id: u32) -> MultiLocation {
+	Parachain(id).into()
+}
+
+fn para_account(id: u32) -> AccountId {
+	ParaId::from(id).into_account()
+}
+
+fn nft(index: u128) -> MultiAsset {
+	(CollectionLocation::get(), Index(index)).into()
+}
+
+#[test]
+fn transfer_moves_ownership() {
+	assert_eq!(Adapter::deposit_asset(&nft(1), &para_location(1000)), Ok(()));
+	assert_eq!(TestCollection::owner(&Index(1)), Some(para_account(1000)));
+
+	// Only the owner can move the instance.
+	assert_eq!(
+		Adapter::transfer_asset(&nft(1), &para_location(2000), &para_location(1000)).map(|_| ()),
+		Err(XcmError::FailedToTransactAsset("NotOwner"))
+	);
+	assert_eq!(
+		Adapter::transfer_asset(&nft(1), &para_location(1000), &para_location(2000)).map(|_| ()),
+		Ok(())
+	);
+	assert_eq!(TestCollection::owner(&Index(1)), Some(para_account(2000)));
+}
+
+#[test]
+fn withdraw_and_teleport_in_work() {
+	assert_eq!(Adapter::deposit_asset(&nft(7), &para_location(1000)), Ok(()));
+	assert_eq!(
+		Adapter::can_check_in(&Parent.into(), &nft(7)),
+		Err(XcmError::FailedToTransactAsset("AlreadyExists"))
+	);
+
+	let withdrawn = Adapter::withdraw_asset(&nft(7), &para_location(1000)).unwrap();
+	assert_eq!(withdrawn, Assets::from(nft(7)));
+	assert_eq!(TestCollection::owner(&Index(7)), None);
+	assert_eq!(Adapter::can_check_in(&Parent.into(), &nft(7)), Ok(()));
+
+	// Fungibles and other collections are not handled.
+	assert_eq!(
+		Adapter::deposit_asset(&(CollectionLocation::get(), 1u128).into(), &para_location(1000)),
+		Err(XcmError::AssetNotFound)
+	);
+	assert_eq!(
+		Adapter::deposit_asset(&(Here, Index(1)).into(), &para_location(1000)),
+		Err(XcmError::AssetNotFound)
+	);
+}
@@ -420,6 +420,10 @@ impl<Config: config::Config> XcmExecutor<Config> {
 				Config::XcmSender::send_xcm(reserve, Xcm(message)).map_err(Into::into)
 			},
 			InitiateTeleport { assets, dest, xcm } => {
+				// Ensure every asset can be checked out before taking any of them out of Holding.
+				for asset in self.holding.min(&assets).assets_iter() {
+					Config::AssetTransactor::can_check_out(&dest, &asset)?;
+				}
 				// We must do this first in order to resolve wildcards.
 				let assets = self.holding.saturating_take(assets);
 				for asset in assets.assets_iter() {
@@ -48,8 +48,21 @@ pub trait TransactAsset {
 	/// value for `_what` which can cause side-effects for more than one of the type-items.
 	fn check_in(_origin: &MultiLocation, _what: &MultiAsset) {}
 
+	/// Ensure that `check_out` will do its bookkeeping properly.
+	///
+	/// Assets which need no bookkeeping when teleported out should result in `Ok`, which is the default.
+	///
+	/// When composed as a tuple, all type-items are called and none may result in an error other than
+	/// `AssetNotFound` or `Unimplemented`.
+	fn can_check_out(_dest: &MultiLocation, _what: &MultiAsset) -> XcmResult {
+		Ok(())
+	}
+
 	/// An asset has been teleported out to the given destination. This should do whatever housekeeping is needed.
 	///
+	/// NOTE: This will make only a best-effort at bookkeeping. The caller should ensure that `can_check_out` has
+	/// returned with `Ok` in order to guarantee that this operation proceeds properly.
+	///
 	/// Implementation note: In general this will do one of two things: On chains where the asset is native,
 	/// it will increase the assets in a special "teleported" account so that a) total-issuance is preserved; and
 	/// b) to ensure that no more assets can be teleported in than were teleported out overall (this should not
@@ -130,6 +143,16 @@ impl TransactAsset for Tuple {
 		)* );
 	}
 
+	fn can_check_out(dest: &MultiLocation, what: &MultiAsset) -> XcmResult {
+		for_tuples!( #(
+			match Tuple::can_check_out(dest, what) {
+				Ok(()) | Err(XcmError::AssetNotFound) | Err(XcmError::Unimplemented) => (),
+				r => return r,
+			}
+		)* );
+		Ok(())
+	}
+
 	fn check_out(dest: &MultiLocation, what: &MultiAsset) {
 		for_tuples!( #(
 			Tuple::check_out(dest, what);
@@ -204,6 +227,10 @@ mod tests {
 			Err(XcmError::AssetNotFound)
 		}
 
+		fn can_check_out(_dest: &MultiLocation, _what: &MultiAsset) -> XcmResult {
+			Err(XcmError::AssetNotFound)
+		}
+
 		fn deposit_asset(_what: &MultiAsset, _who: &MultiLocation) -> XcmResult {
 			Err(XcmError::AssetNotFound)
 		}
@@ -227,6 +254,10 @@ mod tests {
 			Err(XcmError::Overflow)
 		}
 
+		fn can_check_out(_dest: &MultiLocation, _what: &MultiAsset) -> XcmResult {
+			Err(XcmError::Overflow)
+		}
+
 		fn deposit_asset(_what: &MultiAsset, _who: &MultiLocation) -> XcmResult {
 			Err(XcmError::Overflow)
 		}
@@ -301,4 +332,16 @@ mod tests {
 
 		assert_eq!(MultiTransactor::deposit_asset(&(Here, 1).into(), &Here.into()), Ok(()),);
 	}
+
+	#[test]
+	fn can_check_out_requires_no_unexpected_error() {
+		type MultiTransactor = (UnimplementedTransactor, NotFoundTransactor, SuccessfulTransactor);
+		assert_eq!(MultiTransactor::can_check_out(&Here.into(), &(Here, 1).into()), Ok(()));
+
+		type FailingTransactor = (SuccessfulTransactor, OverflowTransactor);
+		assert_eq!(
+			FailingTransactor::can_check_out(&Here.into(), &(Here, 1).into()),
+			Err(XcmError::Overflow)
+		);
+	}
 }